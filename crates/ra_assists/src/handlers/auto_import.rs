@@ -55,7 +55,7 @@ pub(crate) fn auto_import(acc: &mut Assists, ctx: &AssistContext) -> Option<()>
                 insert_use_statement(
                     &auto_import_assets.syntax_under_caret,
                     &import,
-                    ctx,
+                    &ctx.sema,
                     builder.text_edit_builder(),
                 );
             },
@@ -107,7 +107,7 @@ fn insert_import(
     if let Some(mut mod_path) = mod_path {
         mod_path.segments.pop();
         mod_path.segments.push(variant_hir_name.clone());
-        insert_use_statement(path.syntax(), &mod_path, ctx, builder.text_edit_builder());
+        insert_use_statement(path.syntax(), &mod_path, &ctx.sema, builder.text_edit_builder());
     }
     Some(())
 }
@@ -209,10 +209,9 @@ fn list_with_visibility(list: &str) -> String {
 #[cfg(test)]
 mod tests {
 
-    use crate::{
-        tests::{check_assist, check_assist_not_applicable},
-        utils::FamousDefs,
-    };
+    use ra_ide_db::helpers::FamousDefs;
+
+    use crate::tests::{check_assist, check_assist_not_applicable};
 
     use super::*;
 
@@ -1,8 +1,8 @@
-use ra_ide_db::RootDatabase;
+use ra_ide_db::{helpers::FamousDefs, RootDatabase};
 use ra_syntax::ast::{self, AstNode, NameOwner};
 use test_utils::mark;
 
-use crate::{utils::FamousDefs, AssistContext, AssistId, AssistKind, Assists};
+use crate::{AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: generate_from_impl_for_enum
 //
@@ -42,11 +42,16 @@ pub(crate) fn replace_qualified_name_with_use(
         target,
         |builder| {
             let path_to_import = hir_path.mod_path().clone();
-            let container = match find_insert_use_container(path.syntax(), ctx) {
+            let container = match find_insert_use_container(path.syntax(), &ctx.sema) {
                 Some(c) => c,
                 None => return,
             };
-            insert_use_statement(path.syntax(), &path_to_import, ctx, builder.text_edit_builder());
+            insert_use_statement(
+                path.syntax(),
+                &path_to_import,
+                &ctx.sema,
+                builder.text_edit_builder(),
+            );
 
             // Now that we've brought the name into scope, re-qualify all paths that could be
             // affected (that is, all paths inside the node we added the `use` to).
@@ -2,24 +2,24 @@
 // FIXME: rewrite according to the plan, outlined in
 // https://github.com/rust-analyzer/rust-analyzer/issues/3301#issuecomment-592931553
 
-use hir::{self, ModPath};
+use hir::{self, ModPath, Semantics};
+use ra_ide_db::RootDatabase;
 use ra_syntax::{
     ast::{self, NameOwner},
     AstNode, Direction, SmolStr,
     SyntaxKind::{PATH, PATH_SEGMENT},
     SyntaxNode, T,
 };
-use ra_text_edit::TextEditBuilder;
+use ra_text_edit::{TextEdit, TextEditBuilder};
 
-use crate::assist_context::AssistContext;
 use either::Either;
 
 /// Determines the containing syntax node in which to insert a `use` statement affecting `position`.
 pub(crate) fn find_insert_use_container(
     position: &SyntaxNode,
-    ctx: &AssistContext,
+    sema: &Semantics<RootDatabase>,
 ) -> Option<Either<ast::ItemList, ast::SourceFile>> {
-    ctx.sema.ancestors_with_macros(position.clone()).find_map(|n| {
+    sema.ancestors_with_macros(position.clone()).find_map(|n| {
         if let Some(module) = ast::Module::cast(n.clone()) {
             return module.item_list().map(|it| Either::Left(it));
         }
@@ -30,15 +30,15 @@ pub(crate) fn find_insert_use_container(
 /// Creates and inserts a use statement for the given path to import.
 /// The use statement is inserted in the scope most appropriate to the
 /// the cursor position given, additionally merged with the existing use imports.
-pub(crate) fn insert_use_statement(
+pub fn insert_use_statement(
     // Ideally the position of the cursor, used to
     position: &SyntaxNode,
     path_to_import: &ModPath,
-    ctx: &AssistContext,
+    sema: &Semantics<RootDatabase>,
     builder: &mut TextEditBuilder,
 ) {
     let target = path_to_import.to_string().split("::").map(SmolStr::new).collect::<Vec<_>>();
-    let container = find_insert_use_container(position, ctx);
+    let container = find_insert_use_container(position, sema);
 
     if let Some(container) = container {
         let syntax = container.either(|l| l.syntax().clone(), |r| r.syntax().clone());
@@ -47,6 +47,17 @@ pub(crate) fn insert_use_statement(
     }
 }
 
+/// Like `insert_use_statement`, but returns the edit, for use outside of assists.
+pub fn use_statement_edit(
+    position: &SyntaxNode,
+    path_to_import: &ModPath,
+    sema: &Semantics<RootDatabase>,
+) -> TextEdit {
+    let mut builder = TextEditBuilder::default();
+    insert_use_statement(position, path_to_import, sema, &mut builder);
+    builder.finish()
+}
+
 fn collect_path_segments_raw(
     segments: &mut Vec<ast::PathSegment>,
     mut path: ast::Path,
@@ -1,14 +1,15 @@
 //! FIXME: write short doc here
-use std::sync::Arc;
+use std::{iter, sync::Arc};
 
 use arrayvec::ArrayVec;
 use either::Either;
 use hir_def::{
     adt::StructKind,
     adt::VariantData,
-    builtin_type::BuiltinType,
+    builtin_type::{BuiltinFloat, BuiltinInt, BuiltinType},
     docs::Documentation,
     expr::{BindingAnnotation, Pat, PatId},
+    generics::WherePredicateTarget,
     import_map,
     per_ns::PerNs,
     resolver::{HasResolver, Resolver},
@@ -29,7 +30,7 @@ use hir_ty::{
     method_resolution,
     unsafe_validation::UnsafeValidator,
     ApplicationTy, Canonical, GenericPredicate, InEnvironment, Substs, TraitEnvironment, Ty,
-    TyDefId, TypeCtor,
+    TyDefId, TypeCtor, TypeWalk,
 };
 use ra_db::{CrateId, Edition, FileId};
 use ra_prof::profile;
@@ -86,6 +87,12 @@ impl Crate {
         Some(Module::new(self, module_id))
     }
 
+    /// The module whose items are implicitly in scope in this crate, usually
+    /// `std::prelude::v1`.
+    pub fn prelude(self, db: &dyn HirDatabase) -> Option<Module> {
+        db.crate_def_map(self.id).prelude().map(Module::from)
+    }
+
     pub fn root_file(self, db: &dyn HirDatabase) -> FileId {
         db.crate_graph()[self.id].root_file_id
     }
@@ -115,6 +122,22 @@ impl Crate {
         })
     }
 
+    /// Traits exported by this crate and its dependencies which have a method
+    /// whose name starts with `prefix`, according to their import maps.
+    pub fn exported_traits_with_method(self, db: &dyn HirDatabase, prefix: &str) -> Vec<Trait> {
+        let mut res = Vec::new();
+        let mut seen = FxHashSet::default();
+        for krate in iter::once(self).chain(self.dependencies(db).into_iter().map(|it| it.krate)) {
+            let import_map = db.import_map(krate.id);
+            for tr in import_map.traits_with_method_prefix(prefix) {
+                if seen.insert(tr) {
+                    res.push(Trait::from(tr));
+                }
+            }
+        }
+        res
+    }
+
     pub fn all(db: &dyn HirDatabase) -> Vec<Crate> {
         db.crate_graph().iter().map(|id| Crate { id }).collect()
     }
@@ -667,14 +690,93 @@ impl Function {
         db.function_data(self.id).has_self_param
     }
 
+    pub fn ret_type(self, db: &dyn HirDatabase) -> Type {
+        let substs = Substs::type_params(db, self.id);
+        let sig = db.callable_item_signature(self.id.into()).subst(&substs);
+        Type::new(db, self.module(db).id.krate, self.id, sig.ret().clone())
+    }
+
     pub fn params(self, db: &dyn HirDatabase) -> Vec<TypeRef> {
         db.function_data(self.id).params.clone()
     }
 
+    /// The parameter types, including the one of `self`, and the return type
+    /// of this trait method when called on `self_ty`, with `Self` replaced by
+    /// `self_ty`. The associated types of `Self`, like `Self::Item`, are
+    /// replaced by their values in `impl_def`, the impl of the trait for
+    /// `self_ty`, if given.
+    pub fn signature_for_self_ty(
+        self,
+        db: &dyn HirDatabase,
+        self_ty: &Type,
+        impl_def: Option<ImplDef>,
+    ) -> Option<(Vec<Type>, Type)> {
+        let trait_ = match self.id.lookup(db.upcast()).container {
+            AssocContainerId::TraitId(it) => it,
+            _ => return None,
+        };
+        let self_param = TypeParamId {
+            parent: trait_.into(),
+            local_id: db.generic_params(trait_.into()).find_trait_self_param()?,
+        };
+        let trait_data = db.trait_data(trait_);
+        let assoc_tys: Vec<(TypeAliasId, Ty)> = impl_def
+            .into_iter()
+            .flat_map(|it| db.impl_data(it.id).items.clone())
+            .filter_map(|item| match item {
+                AssocItemId::TypeAliasId(alias) => {
+                    let name = &db.type_alias_data(alias).name;
+                    let trait_alias = trait_data.associated_type_by_name(name)?;
+                    let ty = db.ty(alias.into()).subst(&Substs::type_params(db, alias));
+                    Some((trait_alias, ty))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let substs = Substs::type_params(db, self.id);
+        let sig = db.callable_item_signature(self.id.into()).subst(&substs);
+        let krate = self.module(db).id.krate;
+        // The types are folded bottom-up, so `Self` in `Self::Item` is already
+        // replaced when the projection is reached.
+        let substitute = |ty: &Ty| {
+            let ty = ty.clone().fold(&mut |ty| match ty {
+                Ty::Placeholder(id) if id == self_param => self_ty.ty.value.clone(),
+                Ty::Projection(proj) if proj.parameters.first() == Some(&self_ty.ty.value) => {
+                    match assoc_tys.iter().find(|(alias, _)| *alias == proj.associated_ty) {
+                        Some((_, ty)) => ty.clone(),
+                        None => Ty::Projection(proj),
+                    }
+                }
+                ty => ty,
+            });
+            Type::new(db, krate, self.id, ty)
+        };
+        Some((sig.params().iter().map(substitute).collect(), substitute(sig.ret())))
+    }
+
     pub fn is_unsafe(self, db: &dyn HirDatabase) -> bool {
         db.function_data(self.id).is_unsafe
     }
 
+    /// Whether this method can be called on a trait object. Generic methods
+    /// and methods requiring `Self: Sized` can't be.
+    pub fn is_dispatchable(self, db: &dyn HirDatabase) -> bool {
+        let generic_params = db.generic_params(self.id.into());
+        if !generic_params.types.is_empty() {
+            return false;
+        }
+        let self_type = TypeRef::Path(name![Self].into());
+        // FIXME: resolve the bound instead of comparing names, the bound may
+        // be spelled like `core::marker::Sized`.
+        let requires_sized = generic_params.where_predicates.iter().any(|pred| {
+            matches!(&pred.target, WherePredicateTarget::TypeRef(it) if *it == self_type)
+                && pred.bound.as_path().and_then(|it| it.mod_path().segments.last())
+                    == Some(&name![Sized])
+        });
+        !requires_sized && self.has_self_param(db)
+    }
+
     pub fn diagnostics(self, db: &dyn HirDatabase, sink: &mut DiagnosticSink) {
         let _p = profile("Function::diagnostics");
         let infer = db.infer(self.id.into());
@@ -711,6 +813,11 @@ impl Const {
     pub fn name(self, db: &dyn HirDatabase) -> Option<Name> {
         db.const_data(self.id).name.clone()
     }
+
+    pub fn ty(self, db: &dyn HirDatabase) -> Type {
+        let ty = db.value_ty(self.id.into()).value;
+        Type::new(db, self.module(db).id.krate, self.id, ty)
+    }
 }
 
 impl HasVisibility for Const {
@@ -765,6 +872,11 @@ impl Trait {
     pub fn is_auto(self, db: &dyn HirDatabase) -> bool {
         db.trait_data(self.id).auto
     }
+
+    /// Traits named as bounds on `Self`, like `Eq` in `trait Ord: Eq`.
+    pub fn direct_super_traits(self, db: &dyn HirDatabase) -> Vec<Trait> {
+        hir_ty::direct_super_traits(db.upcast(), self.id).into_iter().map(Trait::from).collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1152,6 +1264,12 @@ impl Type {
         Type { krate, ty: InEnvironment { value: ty, environment } }
     }
 
+    /// The type of a builtin, like `i32`, as seen from `module`.
+    pub fn builtin(db: &dyn HirDatabase, module: Module, builtin: BuiltinType) -> Type {
+        let ty = db.ty(TyDefId::BuiltinType(builtin)).value;
+        Type::new(db, module.id.krate, module.id, ty)
+    }
+
     fn from_def(
         db: &dyn HirDatabase,
         krate: CrateId,
@@ -1166,6 +1284,39 @@ impl Type {
         matches!(self.ty.value, Ty::Apply(ApplicationTy { ctor: TypeCtor::Bool, .. }))
     }
 
+    /// If this is a builtin type, like `u8` or `str`, returns it.
+    pub fn as_builtin(&self) -> Option<BuiltinType> {
+        let builtin = match &self.ty.value {
+            Ty::Apply(ApplicationTy { ctor, .. }) => match ctor {
+                TypeCtor::Bool => BuiltinType::Bool,
+                TypeCtor::Char => BuiltinType::Char,
+                TypeCtor::Str => BuiltinType::Str,
+                TypeCtor::Int(it) => {
+                    BuiltinType::Int(BuiltinInt { signedness: it.signedness, bitness: it.bitness })
+                }
+                TypeCtor::Float(it) => BuiltinType::Float(BuiltinFloat { bitness: it.bitness }),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(builtin)
+    }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(
+            self.ty.value,
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Tuple { cardinality: 0 }, .. })
+        )
+    }
+
+    pub fn is_slice(&self) -> bool {
+        matches!(self.ty.value, Ty::Apply(ApplicationTy { ctor: TypeCtor::Slice, .. }))
+    }
+
+    pub fn is_integral(&self) -> bool {
+        matches!(self.ty.value, Ty::Apply(ApplicationTy { ctor: TypeCtor::Int(_), .. }))
+    }
+
     pub fn is_mutable_reference(&self) -> bool {
         matches!(
             self.ty.value,
@@ -1173,6 +1324,11 @@ impl Type {
         )
     }
 
+    /// If this is a reference type, returns the type it refers to.
+    pub fn remove_ref(&self) -> Option<Type> {
+        self.ty.value.as_reference().map(|(ty, _)| self.derived(ty.clone()))
+    }
+
     pub fn is_unknown(&self) -> bool {
         matches!(self.ty.value, Ty::Unknown)
     }
@@ -1199,6 +1355,19 @@ impl Type {
         )
     }
 
+    /// The `Output` of the `impl Future` this type stands for, like the type
+    /// an `async fn` evaluates to when awaited.
+    pub fn future_output(&self, db: &dyn HirDatabase) -> Option<Type> {
+        let future_trait = db.lang_item(self.krate, "future_trait".into())?.as_trait()?;
+        let output = db.trait_data(future_trait).associated_type_by_name(&name![Output])?;
+        self.ty.value.impl_trait_bounds(db)?.into_iter().find_map(|pred| match pred {
+            GenericPredicate::Projection(it) if it.projection_ty.associated_ty == output => {
+                Some(self.derived(it.ty))
+            }
+            _ => None,
+        })
+    }
+
     pub fn impls_trait(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) -> bool {
         let trait_ref = hir_ty::TraitRef {
             trait_: trait_.id,
@@ -1285,6 +1454,14 @@ impl Type {
         res
     }
 
+    /// The generic arguments of this type, like `i32` for `Option<i32>`.
+    pub fn type_arguments(&self) -> Vec<Type> {
+        match &self.ty.value {
+            Ty::Apply(a_ty) => a_ty.parameters.iter().map(|ty| self.derived(ty.clone())).collect(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn autoderef<'a>(&'a self, db: &'a dyn HirDatabase) -> impl Iterator<Item = Type> + 'a {
         // There should be no inference vars in types passed here
         // FIXME check that?
@@ -1324,7 +1501,7 @@ impl Type {
         krate: Crate,
         traits_in_scope: &FxHashSet<TraitId>,
         name: Option<&Name>,
-        mut callback: impl FnMut(&Ty, Function) -> Option<T>,
+        mut callback: impl FnMut(Type, Function) -> Option<T>,
     ) -> Option<T> {
         // There should be no inference vars in types passed here
         // FIXME check that?
@@ -1343,7 +1520,7 @@ impl Type {
             name,
             method_resolution::LookupMode::MethodCall,
             |ty, it| match it {
-                AssocItemId::FunctionId(f) => callback(ty, f.into()),
+                AssocItemId::FunctionId(f) => callback(self.derived(ty.clone()), f.into()),
                 _ => None,
             },
         )
@@ -1403,6 +1580,16 @@ impl Type {
         self.ty.value.associated_type_parent_trait(db).map(Into::into)
     }
 
+    /// Whether `self` and `other` can be the same type, where unknown types
+    /// match anything.
+    pub fn could_unify_with(&self, other: &Type) -> bool {
+        let tys = Canonical {
+            value: (self.ty.value.clone(), other.ty.value.clone()),
+            kinds: Arc::new([]),
+        };
+        hir_ty::unify(&tys).is_some()
+    }
+
     // FIXME: provide required accessors such that it becomes implementable from outside.
     pub fn is_equal_for_find_impls(&self, other: &Type) -> bool {
         match (&self.ty.value, &other.ty.value) {
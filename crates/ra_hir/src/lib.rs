@@ -64,7 +64,7 @@ pub use hir_def::{
     adt::StructKind,
     attr::Attrs,
     body::scope::ExprScopes,
-    builtin_type::BuiltinType,
+    builtin_type::{BuiltinFloat, BuiltinInt, BuiltinType, FloatBitness, IntBitness, Signedness},
     docs::Documentation,
     nameres::ModuleSource,
     path::{ModPath, Path, PathKind},
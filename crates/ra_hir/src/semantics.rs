@@ -260,6 +260,10 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.scope_for_def(def)
     }
 
+    pub fn scope_for_module(&self, module: Module) -> SemanticsScope<'db> {
+        self.imp.scope_for_module(module)
+    }
+
     pub fn assert_contains_node(&self, node: &SyntaxNode) {
         self.imp.assert_contains_node(node)
     }
@@ -455,6 +459,11 @@ impl<'db> SemanticsImpl<'db> {
         SemanticsScope { db: self.db, resolver }
     }
 
+    pub fn scope_for_module(&self, module: Module) -> SemanticsScope<'db> {
+        let resolver = module.id.resolver(self.db.upcast());
+        SemanticsScope { db: self.db, resolver }
+    }
+
     fn analyze(&self, node: &SyntaxNode) -> SourceAnalyzer {
         let src = self.find_file(node.clone());
         self.analyze2(src.as_ref(), None)
@@ -185,6 +185,25 @@ impl<'a> AttrQuery<'a> {
         })
     }
 
+    /// Looks up a `key = "value"` pair inside of the attribute's token tree,
+    /// like `feature` in `#[unstable(feature = "foo")]`.
+    pub fn key_value(self, key: &str) -> Option<SmolStr> {
+        use tt::{Leaf, TokenTree};
+
+        self.tt_values().find_map(|tt| {
+            tt.token_trees.windows(3).find_map(|window| match window {
+                [
+                    TokenTree::Leaf(Leaf::Ident(ident)),
+                    TokenTree::Leaf(Leaf::Punct(punct)),
+                    TokenTree::Leaf(Leaf::Literal(lit)),
+                ] if ident.text == key && punct.char == '=' => {
+                    Some(lit.text.trim_matches('"').into())
+                }
+                _ => None,
+            })
+        })
+    }
+
     pub fn exists(self) -> bool {
         self.attrs().next().is_some()
     }
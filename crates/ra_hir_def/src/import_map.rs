@@ -52,6 +52,10 @@ pub struct ImportMap {
     /// Maps names of associated items to the item's ID. Only includes items whose defining trait is
     /// exported.
     assoc_map: FxHashMap<SmolStr, SmallVec<[AssocItemId; 1]>>,
+
+    /// Names of the methods of exported traits along with the trait, sorted by name. Allows
+    /// finding the traits providing a method by a prefix of its name.
+    trait_methods: Vec<(SmolStr, TraitId)>,
 }
 
 impl ImportMap {
@@ -147,6 +151,7 @@ impl ImportMap {
 
         import_map.fst = fst::Map::new(builder.into_inner().unwrap()).unwrap();
         import_map.importables = importables.iter().map(|(item, _)| **item).collect();
+        import_map.trait_methods.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
 
         Arc::new(import_map)
     }
@@ -160,10 +165,36 @@ impl ImportMap {
         self.map.get(&item)
     }
 
+    /// Returns the exported traits with a method whose name starts with `prefix`. The methods
+    /// can be called once the trait is imported.
+    pub fn traits_with_method_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = TraitId> + 'a {
+        // Finds the first method whose name is not less than `prefix`.
+        let start = self
+            .trait_methods
+            .binary_search_by(|(name, _)| {
+                if name.as_str() < prefix {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_or_else(|idx| idx);
+        self.trait_methods[start..]
+            .iter()
+            .take_while(move |(name, _)| name.starts_with(prefix))
+            .map(|&(_, tr)| tr)
+    }
+
     fn collect_trait_methods(&mut self, db: &dyn DefDatabase, tr: TraitId) {
         let data = db.trait_data(tr);
         for (name, item) in data.items.iter() {
             self.assoc_map.entry(name.to_string().into()).or_default().push(*item);
+            if let AssocItemId::FunctionId(_) = item {
+                self.trait_methods.push((name.to_string().into(), tr));
+            }
         }
     }
 }
@@ -182,6 +182,7 @@ pub mod known {
         Neg,
         Not,
         Index,
+        Sized,
         // Builtin macros
         file,
         column,
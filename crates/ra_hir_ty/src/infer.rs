@@ -45,7 +45,7 @@ use crate::{
     db::HirDatabase, infer::diagnostics::InferenceDiagnostic, lower::ImplTraitLoweringMode,
 };
 
-pub(crate) use unify::unify;
+pub use unify::unify;
 
 macro_rules! ty_app {
     ($ctor:pat, $param:pat) => {
@@ -65,13 +65,14 @@ use crate::{
 use display::HirDisplay;
 
 pub use autoderef::autoderef;
-pub use infer::{InferTy, InferenceResult};
+pub use infer::{unify, InferTy, InferenceResult};
 pub use lower::CallableDef;
 pub use lower::{
     associated_type_shorthand_candidates, callable_item_sig, ImplTraitLoweringMode, TyDefId,
     TyLoweringContext, ValueTyDefId,
 };
 pub use traits::{InEnvironment, Obligation, ProjectionPredicate, TraitEnvironment};
+pub use utils::direct_super_traits;
 
 pub use chalk_ir::{BoundVar, DebruijnIndex};
 use itertools::Itertools;
@@ -2,6 +2,7 @@ mod completion_config;
 mod completion_item;
 mod completion_context;
 mod presentation;
+mod fuzzy;
 
 mod complete_attribute;
 mod complete_dot;
@@ -15,10 +16,18 @@ mod complete_unqualified_path;
 mod complete_postfix;
 mod complete_macro_in_item_position;
 mod complete_trait_impl;
+mod complete_lifetime;
+mod complete_loop_label;
+mod complete_match_arms;
+mod complete_format_spec;
+mod complete_enum_discriminant;
+mod complete_where_clause;
 mod patterns;
 #[cfg(test)]
 mod test_utils;
 
+use ::test_utils::mark;
+use hir::Documentation;
 use ra_ide_db::RootDatabase;
 
 use crate::{
@@ -30,8 +39,14 @@ use crate::{
 };
 
 pub use crate::completion::{
-    completion_config::CompletionConfig,
-    completion_item::{CompletionItem, CompletionItemKind, CompletionScore, InsertTextFormat},
+    completion_config::{
+        CallParens, CallParensPerKind, CompletionConfig, CompletionItemHook, DocumentationFormat,
+        MemberPreference,
+    },
+    completion_item::{
+        CompletionItem, CompletionItemKind, CompletionRelevance, CompletionScore, ImportEdit,
+        InsertTextFormat,
+    },
 };
 
 //FIXME: split the following feature into fine-grained features.
@@ -105,38 +120,313 @@ pub(crate) fn completions(
     db: &RootDatabase,
     config: &CompletionConfig,
     position: FilePosition,
-) -> Option<Completions> {
+) -> Option<Completions<'static>> {
+    let mut acc = Completions::default();
+    complete(&mut acc, db, config, position)?;
+    Some(acc)
+}
+
+/// The single best completion at `position`, for editors which show one
+/// inline suggestion. Only items with a `CompletionScore` are considered, so
+/// this is `None` unless some item matches the expected type; ties go to the
+/// item produced first.
+pub(crate) fn primary_completion(
+    db: &RootDatabase,
+    config: &CompletionConfig,
+    position: FilePosition,
+) -> Option<CompletionItem> {
+    let completions: Vec<CompletionItem> = completions(db, config, position)?.into();
+    let mut best: Option<CompletionItem> = None;
+    for item in completions {
+        if item.score() > best.as_ref().and_then(|it| it.score()) {
+            best = Some(item);
+        }
+    }
+    best
+}
+
+/// The full documentation of the item at `index` in the list computed by
+/// `completions`, for items with `CompletionItem::has_more_documentation`.
+/// Labels are not unique, so items are identified by their index, and `label`
+/// only guards against the completions having changed since.
+pub(crate) fn completion_documentation(
+    db: &RootDatabase,
+    config: &CompletionConfig,
+    position: FilePosition,
+    index: usize,
+    label: &str,
+) -> Option<Documentation> {
+    let config = CompletionConfig { full_documentation: true, ..config.clone() };
+    let completions: Vec<CompletionItem> = completions(db, &config, position)?.into();
+    let item = completions.into_iter().nth(index)?;
+    if item.label() != label {
+        return None;
+    }
+    item.documentation()
+}
+
+/// Like `completions`, but hands each item to `sink` as soon as it is
+/// produced instead of collecting them. Returning `false` from `sink` drops
+/// the following items and skips the remaining completers, which allows
+/// callers to cap the number of items.
+pub(crate) fn completions_streaming(
+    db: &RootDatabase,
+    config: &CompletionConfig,
+    position: FilePosition,
+    sink: &mut dyn FnMut(CompletionItem) -> bool,
+) {
+    let mut acc = Completions::streaming(sink);
+    complete(&mut acc, db, config, position);
+}
+
+fn complete(
+    acc: &mut Completions,
+    db: &RootDatabase,
+    config: &CompletionConfig,
+    position: FilePosition,
+) -> Option<()> {
     let ctx = CompletionContext::new(db, position, config)?;
+    acc.set_hook(config.item_hook.clone());
+    acc.set_summarize_docs(!config.full_documentation);
+    acc.set_plain_text_docs(config.documentation_format == DocumentationFormat::PlainText);
+    if ctx.has_type_context() {
+        acc.set_min_score(config.min_score);
+    }
 
-    let mut acc = Completions::default();
-    complete_attribute::complete_attribute(&mut acc, &ctx);
-    complete_fn_param::complete_fn_param(&mut acc, &ctx);
-    complete_keyword::complete_expr_keyword(&mut acc, &ctx);
-    complete_keyword::complete_use_tree_keyword(&mut acc, &ctx);
-    complete_snippet::complete_expr_snippet(&mut acc, &ctx);
-    complete_snippet::complete_item_snippet(&mut acc, &ctx);
-    complete_qualified_path::complete_qualified_path(&mut acc, &ctx);
-    complete_unqualified_path::complete_unqualified_path(&mut acc, &ctx);
-    complete_dot::complete_dot(&mut acc, &ctx);
-    complete_record::complete_record(&mut acc, &ctx);
-    complete_pattern::complete_pattern(&mut acc, &ctx);
-    complete_postfix::complete_postfix(&mut acc, &ctx);
-    complete_macro_in_item_position::complete_macro_in_item_position(&mut acc, &ctx);
-    complete_trait_impl::complete_trait_impl(&mut acc, &ctx);
+    // Skips the remaining completers once a streaming sink wants no more items.
+    macro_rules! run_completers {
+        ($($completer:path),* $(,)?) => {$(
+            if acc.is_done() {
+                mark::hit!(skips_completers_after_streaming_stops);
+                return Some(());
+            }
+            $completer(acc, &ctx);
+        )*};
+    }
+    run_completers![
+        complete_attribute::complete_attribute,
+        complete_fn_param::complete_fn_param,
+        complete_keyword::complete_expr_keyword,
+        complete_keyword::complete_use_tree_keyword,
+        complete_keyword::complete_path_root_keyword,
+        complete_snippet::complete_expr_snippet,
+        complete_snippet::complete_item_snippet,
+        complete_match_arms::complete_match_arms,
+        complete_match_arms::complete_wildcard_arm,
+        complete_match_arms::complete_matches_macro_pattern,
+        complete_qualified_path::complete_qualified_path,
+        complete_unqualified_path::complete_unqualified_path,
+        complete_dot::complete_dot,
+        complete_record::complete_record,
+        complete_pattern::complete_pattern,
+        complete_postfix::complete_postfix,
+        complete_macro_in_item_position::complete_macro_in_item_position,
+        complete_trait_impl::complete_trait_impl,
+        complete_lifetime::complete_lifetime,
+        complete_loop_label::complete_loop_label,
+        complete_format_spec::complete_format_spec,
+        complete_enum_discriminant::complete_enum_discriminant,
+        complete_where_clause::complete_where_clause,
+    ];
 
-    Some(acc)
+    Some(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::completion::completion_config::CompletionConfig;
+    use crate::completion::{
+        completion_config::{CompletionConfig, CompletionItemHook, DocumentationFormat},
+        CompletionItem, CompletionScore,
+    };
     use crate::mock_analysis::analysis_and_position;
+    use ::test_utils::mark;
 
     struct DetailAndDocumentation<'a> {
         detail: &'a str,
         documentation: &'a str,
     }
 
+    #[test]
+    fn streaming_completions_match_collected_ones() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+struct S { foo: u32 }
+fn bar() {}
+fn main() { let s = S { foo: 92 }; <|> }
+"#,
+        );
+        let config = CompletionConfig::default();
+        let collected = analysis.completions(&config, position).unwrap().unwrap();
+
+        let mut streamed = Vec::new();
+        analysis
+            .completions_streaming(&config, position, &mut |item| {
+                streamed.push(item);
+                true
+            })
+            .unwrap();
+
+        let labels = |items: &[CompletionItem]| -> Vec<String> {
+            items.iter().map(|it| it.label().to_string()).collect()
+        };
+        assert!(collected.len() > 1);
+        assert_eq!(labels(&collected), labels(&streamed));
+
+        mark::check!(skips_completers_after_streaming_stops);
+        let mut limited = Vec::new();
+        analysis
+            .completions_streaming(&config, position, &mut |item| {
+                limited.push(item);
+                limited.len() < 2
+            })
+            .unwrap();
+        assert_eq!(labels(&collected[..2]), labels(&limited));
+    }
+
+    #[test]
+    fn primary_completion_is_the_top_scored_item() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+fn take(width: u32) {}
+fn main() {
+    let height = 1u32;
+    let width = 2u32;
+    let name = "";
+    take(<|>)
+}
+"#,
+        );
+        let config = CompletionConfig::default();
+        let primary = analysis.primary_completion(&config, position).unwrap().unwrap();
+        assert_eq!(primary.label(), "width");
+        assert_eq!(primary.score(), Some(CompletionScore::TypeAndNameMatch));
+
+        let (analysis, position) = analysis_and_position(
+            r#"
+fn main() { let x = 92; <|> }
+"#,
+        );
+        assert!(analysis.primary_completion(&config, position).unwrap().is_none());
+    }
+
+    #[test]
+    fn min_score_drops_unscored_items() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+fn take(width: u32) {}
+fn main() {
+    let height = 1u32;
+    let width = 2u32;
+    let name = "";
+    take(<|>)
+}
+"#,
+        );
+        let config = CompletionConfig {
+            min_score: Some(CompletionScore::TypeMatch),
+            ..CompletionConfig::default()
+        };
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, vec!["width", "height"]);
+
+        let config = CompletionConfig {
+            min_score: Some(CompletionScore::TypeAndNameMatch),
+            ..CompletionConfig::default()
+        };
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, vec!["width"]);
+
+        let (analysis, position) = analysis_and_position(
+            r#"
+fn main() { <|> }
+"#,
+        );
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        assert!(completions.iter().any(|it| it.label() == "main()"));
+    }
+
+    #[test]
+    fn item_hook_rewrites_items() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+fn foo() {}
+fn main() { f<|> }
+"#,
+        );
+        let config = CompletionConfig {
+            item_hook: Some(CompletionItemHook::new(|item| {
+                let label = item.label().to_uppercase();
+                item.set_label(label);
+            })),
+            ..CompletionConfig::default()
+        };
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert!(labels.contains(&"FOO()"));
+        assert!(labels.iter().all(|it| it.to_uppercase() == *it));
+    }
+
+    #[test]
+    fn plain_text_documentation_strips_markdown() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+/// # Frobs
+/// Frobs `x` **twice**, see [`frob_once`](crate::frob_once).
+/// Takes `2 * x` or `x[0]` of [`Vec<T>`], like ``a `b` c``, but _not_ [this.
+///
+/// ```
+/// # use frobs::frob;
+/// frob(1);
+/// ```
+///
+/// ```text
+/// # not hidden
+/// ```
+fn frob(x: u32) {}
+fn main() { fr<|> }
+"#,
+        );
+        let config = CompletionConfig {
+            documentation_format: DocumentationFormat::PlainText,
+            full_documentation: true,
+            ..CompletionConfig::default()
+        };
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        let frob = completions.iter().find(|it| it.label() == "frob(…)").unwrap();
+        assert_eq!(
+            frob.documentation().unwrap().as_str(),
+            "Frobs\nFrobs x twice, see frob_once.\n\
+             Takes 2 * x or x[0] of Vec<T>, like a `b` c, but not [this.\n\n\
+             frob(1);\n\n# not hidden"
+        );
+    }
+
+    #[test]
+    fn inlines_documentation_summary() {
+        let (analysis, position) = analysis_and_position(
+            r#"
+/// Frobs `x` twice.
+///
+/// Frobbing is idempotent.
+fn frob(x: u32) {}
+fn main() { fr<|> }
+"#,
+        );
+        let config = CompletionConfig::default();
+        let completions = analysis.completions(&config, position).unwrap().unwrap();
+        let index = completions.iter().position(|it| it.label() == "frob(…)").unwrap();
+        let frob = &completions[index];
+        assert_eq!(frob.documentation().unwrap().as_str(), "Frobs `x` twice.");
+        assert!(frob.has_more_documentation());
+
+        let docs = analysis.completion_documentation(&config, position, index, "frob(…)").unwrap();
+        assert_eq!(docs.unwrap().as_str(), "Frobs `x` twice.\n\nFrobbing is idempotent.");
+        let stale = analysis.completion_documentation(&config, position, index, "main()").unwrap();
+        assert!(stale.is_none());
+    }
+
     fn check_detail_and_documentation(ra_fixture: &str, expected: DetailAndDocumentation) {
         let (analysis, position) = analysis_and_position(ra_fixture);
         let config = CompletionConfig::default();
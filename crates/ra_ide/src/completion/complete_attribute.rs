@@ -3,7 +3,10 @@
 //! This module uses a bit of static metadata to provide completions
 //! for built-in attributes.
 
-use ra_syntax::{ast, AstNode, SyntaxKind};
+use ra_syntax::{
+    ast::{self, NameOwner},
+    AstNode, SyntaxKind,
+};
 use rustc_hash::FxHashSet;
 
 use crate::completion::{
@@ -20,6 +23,22 @@ pub(super) fn complete_attribute(acc: &mut Completions, ctx: &CompletionContext)
         {
             complete_derive(acc, ctx, token_tree)
         }
+        (Some(path), Some(ast::AttrInput::TokenTree(token_tree)))
+            if ["allow", "warn", "deny", "forbid"].contains(&path.to_string().as_str()) =>
+        {
+            complete_lint(acc, ctx, token_tree)
+        }
+        (Some(path), Some(ast::AttrInput::TokenTree(token_tree)))
+            if path.to_string() == "cfg_attr" =>
+        {
+            complete_cfg_attr(acc, ctx, attribute, token_tree)
+        }
+        (Some(path), Some(ast::AttrInput::TokenTree(_token_tree))) => {
+            let path = path.to_string();
+            if let Some(args) = attribute_args(&path) {
+                complete_attribute_args(acc, ctx, args)
+            }
+        }
         (_, Some(ast::AttrInput::TokenTree(_token_tree))) => {}
         _ => complete_attribute_start(acc, ctx, attribute),
     }
@@ -27,6 +46,9 @@ pub(super) fn complete_attribute(acc: &mut Completions, ctx: &CompletionContext)
 }
 
 fn complete_attribute_start(acc: &mut Completions, ctx: &CompletionContext, attribute: &ast::Attr) {
+    // FIXME: this also holds for the root of a non-root module file.
+    let is_crate_root =
+        attribute.syntax().parent().map_or(false, |it| it.kind() == SyntaxKind::SOURCE_FILE);
     for attr_completion in ATTRIBUTES {
         let mut item = CompletionItem::new(
             CompletionKind::Attribute,
@@ -46,10 +68,94 @@ fn complete_attribute_start(acc: &mut Completions, ctx: &CompletionContext, attr
             _ => {}
         }
 
-        if attribute.kind() == ast::AttrKind::Inner || !attr_completion.prefer_inner {
+        let applicable = match attribute.kind() {
+            // Attributes preferring the inner form configure the whole crate.
+            ast::AttrKind::Inner if attr_completion.prefer_inner => is_crate_root,
+            ast::AttrKind::Inner => !attr_completion.outer_only,
+            ast::AttrKind::Outer => !attr_completion.prefer_inner,
+        };
+        if applicable {
             acc.add(item);
         }
     }
+
+    if attribute.kind() == ast::AttrKind::Outer {
+        complete_runtime_main(acc, ctx, attribute);
+    }
+}
+
+/// Completes the attribute macros of async runtimes above `fn main`, like
+/// `tokio::main`, if the runtime is a dependency of the crate.
+fn complete_runtime_main(acc: &mut Completions, ctx: &CompletionContext, attribute: &ast::Attr) {
+    let is_main = attribute
+        .syntax()
+        .parent()
+        .and_then(ast::FnDef::cast)
+        .and_then(|it| it.name())
+        .map_or(false, |it| it.text() == "main");
+    let krate = match ctx.krate {
+        Some(it) if is_main => it,
+        _ => return,
+    };
+    let deps: FxHashSet<String> =
+        krate.dependencies(ctx.db).into_iter().map(|it| it.name.to_string()).collect();
+    for &(runtime, label) in RUNTIME_MAIN_ATTRIBUTES {
+        if deps.contains(runtime) {
+            CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), label)
+                .kind(CompletionItemKind::Attribute)
+                .detail(format!("{} runtime", runtime))
+                .add_to(acc);
+        }
+    }
+}
+
+const RUNTIME_MAIN_ATTRIBUTES: &[(&str, &str)] =
+    &[("tokio", "tokio::main"), ("async_std", "async_std::main")];
+
+/// Completes `cfg_attr(predicate, attr)`: a cfg predicate comes first, and
+/// the attributes to apply follow it.
+fn complete_cfg_attr(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    attribute: &ast::Attr,
+    token_tree: ast::TokenTree,
+) {
+    let preceding_commas = token_tree
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::COMMA && it.text_range().end() <= ctx.offset)
+        .count();
+    if preceding_commas == 0 {
+        if let Some(args) = attribute_args("cfg") {
+            complete_attribute_args(acc, ctx, args)
+        }
+    } else {
+        complete_attribute_start(acc, ctx, attribute)
+    }
+}
+
+fn attribute_args(path: &str) -> Option<&'static [AttrCompletion]> {
+    ATTRIBUTE_ARGS.iter().find(|(attr, _)| *attr == path).map(|(_, args)| *args)
+}
+
+fn complete_attribute_args(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    args: &[AttrCompletion],
+) {
+    for arg in args {
+        let mut item =
+            CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), arg.label)
+                .kind(CompletionItemKind::Attribute);
+        if let Some(lookup) = arg.lookup {
+            item = item.lookup_by(lookup);
+        }
+        if let (Some(snippet), Some(cap)) = (arg.snippet, ctx.config.snippet_cap) {
+            item = item.insert_snippet(cap, snippet);
+        }
+        acc.add(item);
+    }
 }
 
 struct AttrCompletion {
@@ -57,12 +163,19 @@ struct AttrCompletion {
     lookup: Option<&'static str>,
     snippet: Option<&'static str>,
     prefer_inner: bool,
+    /// Attributes which only make sense on an item, and not as `#![...]` in a
+    /// crate or module.
+    outer_only: bool,
 }
 
 impl AttrCompletion {
     const fn prefer_inner(self) -> AttrCompletion {
         AttrCompletion { prefer_inner: true, ..self }
     }
+
+    const fn outer_only(self) -> AttrCompletion {
+        AttrCompletion { outer_only: true, ..self }
+    }
 }
 
 const fn attr(
@@ -70,7 +183,7 @@ const fn attr(
     lookup: Option<&'static str>,
     snippet: Option<&'static str>,
 ) -> AttrCompletion {
-    AttrCompletion { label, lookup, snippet, prefer_inner: false }
+    AttrCompletion { label, lookup, snippet, prefer_inner: false, outer_only: false }
 }
 
 const ATTRIBUTES: &[AttrCompletion] = &[
@@ -79,42 +192,46 @@ const ATTRIBUTES: &[AttrCompletion] = &[
     attr("cfg(…)", Some("cfg"), Some("cfg(${0:predicate})")),
     attr("deny(…)", Some("deny"), Some("deny(${0:lint})")),
     attr(r#"deprecated = "…""#, Some("deprecated"), Some(r#"deprecated = "${0:reason}""#)),
-    attr("derive(…)", Some("derive"), Some(r#"derive(${0:Debug})"#)),
+    attr("derive(…)", Some("derive"), Some(r#"derive(${0:Debug})"#)).outer_only(),
     attr(r#"doc = "…""#, Some("doc"), Some(r#"doc = "${0:docs}""#)),
     attr("feature(…)", Some("feature"), Some("feature(${0:flag})")).prefer_inner(),
     attr("forbid(…)", Some("forbid"), Some("forbid(${0:lint})")),
     // FIXME: resolve through macro resolution?
     attr("global_allocator", None, None).prefer_inner(),
-    attr("ignore(…)", Some("ignore"), Some("ignore(${0:lint})")),
-    attr("inline(…)", Some("inline"), Some("inline(${0:lint})")),
-    attr(r#"link_name = "…""#, Some("link_name"), Some(r#"link_name = "${0:symbol_name}""#)),
-    attr("link", None, None),
-    attr("macro_export", None, None),
+    attr("ignore(…)", Some("ignore"), Some("ignore(${0:lint})")).outer_only(),
+    attr("inline(…)", Some("inline"), Some("inline(${0:lint})")).outer_only(),
+    attr(r#"link_name = "…""#, Some("link_name"), Some(r#"link_name = "${0:symbol_name}""#))
+        .outer_only(),
+    attr("link", None, None).outer_only(),
+    attr("macro_export", None, None).outer_only(),
     attr("macro_use", None, None),
-    attr(r#"must_use = "…""#, Some("must_use"), Some(r#"must_use = "${0:reason}""#)),
-    attr("no_mangle", None, None),
+    attr(r#"must_use = "…""#, Some("must_use"), Some(r#"must_use = "${0:reason}""#)).outer_only(),
+    attr("no_mangle", None, None).outer_only(),
     attr("no_std", None, None).prefer_inner(),
-    attr("non_exhaustive", None, None),
+    attr("non_exhaustive", None, None).outer_only(),
     attr("panic_handler", None, None).prefer_inner(),
-    attr("path = \"…\"", Some("path"), Some("path =\"${0:path}\"")),
-    attr("proc_macro", None, None),
-    attr("proc_macro_attribute", None, None),
-    attr("proc_macro_derive(…)", Some("proc_macro_derive"), Some("proc_macro_derive(${0:Trait})")),
+    attr("path = \"…\"", Some("path"), Some("path =\"${0:path}\"")).outer_only(),
+    attr("proc_macro", None, None).outer_only(),
+    attr("proc_macro_attribute", None, None).outer_only(),
+    attr("proc_macro_derive(…)", Some("proc_macro_derive"), Some("proc_macro_derive(${0:Trait})"))
+        .outer_only(),
     attr("recursion_limit = …", Some("recursion_limit"), Some("recursion_limit = ${0:128}"))
         .prefer_inner(),
-    attr("repr(…)", Some("repr"), Some("repr(${0:C})")),
+    attr("repr(…)", Some("repr"), Some("repr(${0:C})")).outer_only(),
     attr(
         "should_panic(…)",
         Some("should_panic"),
         Some(r#"should_panic(expected = "${0:reason}")"#),
-    ),
+    )
+    .outer_only(),
     attr(
         r#"target_feature = "…""#,
         Some("target_feature"),
         Some("target_feature = \"${0:feature}\""),
-    ),
-    attr("test", None, None),
-    attr("used", None, None),
+    )
+    .outer_only(),
+    attr("test", None, None).outer_only(),
+    attr("used", None, None).outer_only(),
     attr("warn(…)", Some("warn"), Some("warn(${0:lint})")),
     attr(
         r#"windows_subsystem = "…""#,
@@ -124,8 +241,51 @@ const ATTRIBUTES: &[AttrCompletion] = &[
     .prefer_inner(),
 ];
 
+/// Arguments of built-in attributes which only accept a fixed set of values.
+const ATTRIBUTE_ARGS: &[(&str, &[AttrCompletion])] = &[
+    (
+        "repr",
+        &[
+            attr("C", None, None),
+            attr("transparent", None, None),
+            attr("packed", None, None),
+            attr("align(…)", Some("align"), Some("align(${0:8})")),
+            attr("u8", None, None),
+            attr("u16", None, None),
+            attr("u32", None, None),
+            attr("u64", None, None),
+            attr("usize", None, None),
+            attr("i8", None, None),
+            attr("i16", None, None),
+            attr("i32", None, None),
+            attr("i64", None, None),
+            attr("isize", None, None),
+        ],
+    ),
+    (
+        "cfg",
+        &[
+            attr("test", None, None),
+            attr("debug_assertions", None, None),
+            attr("unix", None, None),
+            attr("windows", None, None),
+            attr(r#"feature = "…""#, Some("feature"), Some(r#"feature = "${0:name}""#)),
+            attr(r#"target_os = "…""#, Some("target_os"), Some(r#"target_os = "${0:linux}""#)),
+            attr(
+                r#"target_arch = "…""#,
+                Some("target_arch"),
+                Some(r#"target_arch = "${0:x86_64}""#),
+            ),
+            attr("not(…)", Some("not"), Some("not(${0:predicate})")),
+            attr("all(…)", Some("all"), Some("all(${0:predicates})")),
+            attr("any(…)", Some("any"), Some("any(${0:predicates})")),
+        ],
+    ),
+    ("inline", &[attr("always", None, None), attr("never", None, None)]),
+];
+
 fn complete_derive(acc: &mut Completions, ctx: &CompletionContext, derive_input: ast::TokenTree) {
-    if let Ok(existing_derives) = parse_derive_input(derive_input) {
+    if let Ok(existing_derives) = parse_comma_sep_input(derive_input) {
         for derive_completion in DEFAULT_DERIVE_COMPLETIONS
             .into_iter()
             .filter(|completion| !existing_derives.contains(completion.label))
@@ -158,7 +318,7 @@ fn complete_derive(acc: &mut Completions, ctx: &CompletionContext, derive_input:
     }
 }
 
-fn parse_derive_input(derive_input: ast::TokenTree) -> Result<FxHashSet<String>, ()> {
+fn parse_comma_sep_input(derive_input: ast::TokenTree) -> Result<FxHashSet<String>, ()> {
     match (derive_input.left_delimiter_token(), derive_input.right_delimiter_token()) {
         (Some(left_paren), Some(right_paren))
             if left_paren.kind() == SyntaxKind::L_PAREN
@@ -193,6 +353,25 @@ fn parse_derive_input(derive_input: ast::TokenTree) -> Result<FxHashSet<String>,
     }
 }
 
+fn complete_lint(acc: &mut Completions, ctx: &CompletionContext, lint_input: ast::TokenTree) {
+    if let Ok(existing_lints) = parse_comma_sep_input(lint_input) {
+        for lint_completion in DEFAULT_LINT_COMPLETIONS
+            .iter()
+            .filter(|completion| !existing_lints.contains(completion.label))
+        {
+            acc.add(
+                CompletionItem::new(
+                    CompletionKind::Attribute,
+                    ctx.source_range(),
+                    lint_completion.label,
+                )
+                .kind(CompletionItemKind::Attribute)
+                .detail(lint_completion.description),
+            );
+        }
+    }
+}
+
 fn get_derive_names_in_scope(ctx: &CompletionContext) -> FxHashSet<String> {
     let mut result = FxHashSet::default();
     ctx.scope().process_all_names(&mut |name, scope_def| {
@@ -224,6 +403,63 @@ const DEFAULT_DERIVE_COMPLETIONS: &[DeriveCompletion] = &[
     DeriveCompletion { label: "Ord", dependencies: &["PartialOrd", "Eq", "PartialEq"] },
 ];
 
+struct LintCompletion {
+    label: &'static str,
+    description: &'static str,
+}
+
+/// Commonly used rustc and clippy lints.
+const DEFAULT_LINT_COMPLETIONS: &[LintCompletion] = &[
+    LintCompletion { label: "dead_code", description: "detect unused, unexported items" },
+    LintCompletion { label: "deprecated", description: "detects use of deprecated items" },
+    LintCompletion {
+        label: "missing_docs",
+        description: "detects missing documentation for public members",
+    },
+    LintCompletion {
+        label: "non_camel_case_types",
+        description: "types, variants, traits and type parameters should have camel case names",
+    },
+    LintCompletion {
+        label: "non_snake_case",
+        description: "variables, methods, functions, lifetime parameters and modules should have snake case names",
+    },
+    LintCompletion {
+        label: "non_upper_case_globals",
+        description: "static constants should have uppercase identifiers",
+    },
+    LintCompletion { label: "unreachable_code", description: "detects unreachable code paths" },
+    LintCompletion {
+        label: "unreachable_patterns",
+        description: "detects unreachable patterns",
+    },
+    LintCompletion { label: "unsafe_code", description: "usage of `unsafe` code" },
+    LintCompletion { label: "unused_imports", description: "imports that are never used" },
+    LintCompletion {
+        label: "unused_mut",
+        description: "detect mut variables which don't need to be mutable",
+    },
+    LintCompletion {
+        label: "unused_must_use",
+        description: "unused result of a type flagged as `#[must_use]`",
+    },
+    LintCompletion {
+        label: "unused_variables",
+        description: "detect variables which are not used in any way",
+    },
+    LintCompletion { label: "warnings", description: "all lints that are set to issue warnings" },
+    LintCompletion { label: "clippy::all", description: "all clippy lints enabled by default" },
+    LintCompletion { label: "clippy::pedantic", description: "lints which are rather strict" },
+    LintCompletion {
+        label: "clippy::too_many_arguments",
+        description: "functions with too many parameters",
+    },
+    LintCompletion {
+        label: "clippy::type_complexity",
+        description: "usage of very complex types that might be better factored into `type` definitions",
+    },
+];
+
 #[cfg(test)]
 mod tests {
     use expect::{expect, Expect};
@@ -256,6 +492,83 @@ struct Test {}
         );
     }
 
+    #[test]
+    fn completes_lints() {
+        check(
+            r#"
+#[allow(dead_code, <|>)]
+fn main() {}
+"#,
+            expect![[r##"
+                at clippy::all all clippy lints enabled by default
+                at clippy::pedantic lints which are rather strict
+                at clippy::too_many_arguments functions with too many parameters
+                at clippy::type_complexity usage of very complex types that might be better factored into `type` definitions
+                at deprecated detects use of deprecated items
+                at missing_docs detects missing documentation for public members
+                at non_camel_case_types types, variants, traits and type parameters should have camel case names
+                at non_snake_case variables, methods, functions, lifetime parameters and modules should have snake case names
+                at non_upper_case_globals static constants should have uppercase identifiers
+                at unreachable_code detects unreachable code paths
+                at unreachable_patterns detects unreachable patterns
+                at unsafe_code usage of `unsafe` code
+                at unused_imports imports that are never used
+                at unused_must_use unused result of a type flagged as `#[must_use]`
+                at unused_mut detect mut variables which don't need to be mutable
+                at unused_variables detect variables which are not used in any way
+                at warnings all lints that are set to issue warnings
+            "##]],
+        );
+    }
+
+    #[test]
+    fn completes_repr_args() {
+        check(
+            r#"
+#[repr(<|>)]
+enum E { A }
+"#,
+            expect![[r#"
+                at C
+                at align(…)
+                at i16
+                at i32
+                at i64
+                at i8
+                at isize
+                at packed
+                at transparent
+                at u16
+                at u32
+                at u64
+                at u8
+                at usize
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_cfg_args() {
+        check(
+            r#"
+#[cfg(<|>)]
+mod tests {}
+"#,
+            expect![[r#"
+                at all(…)
+                at any(…)
+                at debug_assertions
+                at feature = "…"
+                at not(…)
+                at target_arch = "…"
+                at target_os = "…"
+                at test
+                at unix
+                at windows
+            "#]],
+        );
+    }
+
     #[test]
     fn no_completion_for_incorrect_derive() {
         check(
@@ -325,13 +638,76 @@ struct Test {}
 
     #[test]
     fn test_attribute_completion_inside_nested_attr() {
-        check(r#"#[allow(<|>)]"#, expect![[]])
+        check(
+            r#"#[allow(<|>)]"#,
+            expect![[r##"
+            at clippy::all all clippy lints enabled by default
+            at clippy::pedantic lints which are rather strict
+            at clippy::too_many_arguments functions with too many parameters
+            at clippy::type_complexity usage of very complex types that might be better factored into `type` definitions
+            at dead_code detect unused, unexported items
+            at deprecated detects use of deprecated items
+            at missing_docs detects missing documentation for public members
+            at non_camel_case_types types, variants, traits and type parameters should have camel case names
+            at non_snake_case variables, methods, functions, lifetime parameters and modules should have snake case names
+            at non_upper_case_globals static constants should have uppercase identifiers
+            at unreachable_code detects unreachable code paths
+            at unreachable_patterns detects unreachable patterns
+            at unsafe_code usage of `unsafe` code
+            at unused_imports imports that are never used
+            at unused_must_use unused result of a type flagged as `#[must_use]`
+            at unused_mut detect mut variables which don't need to be mutable
+            at unused_variables detect variables which are not used in any way
+            at warnings all lints that are set to issue warnings
+        "##]],
+        )
     }
 
     #[test]
-    fn test_inner_attribute_completion() {
+    fn completes_runtime_main_attributes_of_dependencies() {
+        let actual = completion_list(
+            r#"
+//- /main.rs
+#[<|>]
+async fn main() {}
+//- /tokio/lib.rs
+"#,
+            CompletionKind::Attribute,
+        );
+        assert!(actual.contains("at tokio::main tokio runtime"));
+        assert!(!actual.contains("async_std::main"));
+
+        let actual = completion_list(
+            r#"
+//- /main.rs
+#[<|>]
+async fn run() {}
+//- /tokio/lib.rs
+"#,
+            CompletionKind::Attribute,
+        );
+        assert!(!actual.contains("tokio::main"));
+    }
+
+    #[test]
+    fn completes_cfg_attr_predicate_and_attributes() {
         check(
-            r"#![<|>]",
+            r#"#[cfg_attr(<|>)] struct S;"#,
+            expect![[r#"
+                at all(…)
+                at any(…)
+                at debug_assertions
+                at feature = "…"
+                at not(…)
+                at target_arch = "…"
+                at target_os = "…"
+                at test
+                at unix
+                at windows
+            "#]],
+        );
+        check(
+            r#"#[cfg_attr(unix, <|>)] struct S;"#,
             expect![[r#"
                 at allow(…)
                 at cfg(…)
@@ -340,9 +716,7 @@ struct Test {}
                 at deprecated = "…"
                 at derive(…)
                 at doc = "…"
-                at feature(…)
                 at forbid(…)
-                at global_allocator
                 at ignore(…)
                 at inline(…)
                 at link
@@ -351,22 +725,64 @@ struct Test {}
                 at macro_use
                 at must_use = "…"
                 at no_mangle
-                at no_std
                 at non_exhaustive
-                at panic_handler
                 at path = "…"
                 at proc_macro
                 at proc_macro_attribute
                 at proc_macro_derive(…)
-                at recursion_limit = …
                 at repr(…)
                 at should_panic(…)
                 at target_feature = "…"
                 at test
                 at used
                 at warn(…)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_inner_attribute_completion() {
+        check(
+            r"#![<|>]",
+            expect![[r#"
+                at allow(…)
+                at cfg(…)
+                at cfg_attr(…)
+                at deny(…)
+                at deprecated = "…"
+                at doc = "…"
+                at feature(…)
+                at forbid(…)
+                at global_allocator
+                at macro_use
+                at no_std
+                at panic_handler
+                at recursion_limit = …
+                at warn(…)
                 at windows_subsystem = "…"
             "#]],
         );
     }
+
+    #[test]
+    fn test_inner_attribute_completion_in_module() {
+        check(
+            r"
+mod m {
+    #![<|>]
+}
+",
+            expect![[r#"
+                at allow(…)
+                at cfg(…)
+                at cfg_attr(…)
+                at deny(…)
+                at deprecated = "…"
+                at doc = "…"
+                at forbid(…)
+                at macro_use
+                at warn(…)
+            "#]],
+        );
+    }
 }
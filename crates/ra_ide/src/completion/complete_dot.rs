@@ -1,6 +1,11 @@
 //! FIXME: write short doc here
 
-use hir::{HasVisibility, Type};
+use hir::{
+    AsAssocItem, AssocItemContainer, BuiltinInt, BuiltinType, Function, HasVisibility, HirDisplay,
+    ModuleDef, Trait, Type,
+};
+use stdx::SepBy;
+use test_utils::mark;
 
 use crate::{
     completion::{
@@ -18,13 +23,34 @@ pub(super) fn complete_dot(acc: &mut Completions, ctx: &CompletionContext) {
         _ => return,
     };
 
-    let receiver_ty = match ctx.sema.type_of_expr(&dot_receiver) {
-        Some(ty) => ty,
-        _ => return,
+    let receiver_ty = if ctx.dot_receiver_is_ambiguous_float_literal {
+        // `42.<|>` is lexed as a float literal, but the user most likely wants
+        // to call a method on the integer. Without an expected integer type,
+        // the literal falls back to `i32`, like in type inference.
+        mark::hit!(completes_int_methods_on_ambiguous_float_literal);
+        let expected_int = ctx
+            .dot_expected_type
+            .iter()
+            .chain(ctx.expected_type.iter())
+            .find(|ty| ty.is_integral())
+            .cloned();
+        match (expected_int, ctx.scope().module()) {
+            (Some(ty), _) => ty,
+            (None, Some(module)) => {
+                Type::builtin(ctx.db, module, BuiltinType::Int(BuiltinInt::I32))
+            }
+            (None, None) => return,
+        }
+    } else {
+        match ctx.sema.type_of_expr(&dot_receiver) {
+            Some(ty) => ty,
+            _ => return,
+        }
     };
 
     if !ctx.is_call {
         complete_fields(acc, ctx, &receiver_ty);
+        complete_unwrapped_fields(acc, ctx, &receiver_ty);
     }
     complete_methods(acc, ctx, &receiver_ty);
 
@@ -54,31 +80,132 @@ fn complete_fields(acc: &mut Completions, ctx: &CompletionContext, receiver: &Ty
     }
 }
 
+/// Completes the fields of `T` behind an `Option<T>` or `Result<T, _>`
+/// receiver, like `unwrap().name` in `person.<|>`.
+fn complete_unwrapped_fields(acc: &mut Completions, ctx: &CompletionContext, receiver: &Type) {
+    if !ctx.is_result_or_option(receiver) {
+        return;
+    }
+    let inner = match receiver.type_arguments().into_iter().next() {
+        Some(it) => it,
+        None => return,
+    };
+    for (field, ty) in inner.fields(ctx.db) {
+        if ctx.scope().module().map_or(false, |m| !field.is_visible_from(ctx.db, m)) {
+            continue;
+        }
+        acc.add_unwrapped_field(ctx, field, &ty);
+    }
+}
+
 fn complete_methods(acc: &mut Completions, ctx: &CompletionContext, receiver: &Type) {
     if let Some(krate) = ctx.krate {
         let mut seen_methods = FxHashSet::default();
-        let traits_in_scope = ctx.scope().traits_in_scope();
-        receiver.iterate_method_candidates(ctx.db, krate, &traits_in_scope, None, |_ty, func| {
+        let mut traits_in_scope = ctx.scope().traits_in_scope();
+        if ctx.config.enable_autoimport_completions && !receiver.is_unknown() {
+            // Methods of these traits get a `use` for the trait, see `add_function`.
+            for trait_ in importable_traits(ctx) {
+                traits_in_scope.insert(trait_.into());
+            }
+        }
+        // Types the receiver auto-derefs to, ignoring plain references.
+        let deref_chain: Vec<Type> =
+            receiver.autoderef(ctx.db).filter(|ty| ty.remove_ref().is_none()).collect();
+        receiver.iterate_method_candidates(ctx.db, krate, &traits_in_scope, None, |ty, func| {
+            if !is_implemented_for(ctx, func, &ty) {
+                return None;
+            }
+            // Methods which are not object safe can't be called on `dyn Trait`.
+            if ty.as_dyn_trait().is_some() && !func.is_dispatchable(ctx.db) {
+                mark::hit!(skips_non_dispatchable_methods_of_trait_objects);
+                return None;
+            }
             if func.has_self_param(ctx.db)
                 && ctx.scope().module().map_or(true, |m| func.is_visible_from(ctx.db, m))
                 && seen_methods.insert(func.name(ctx.db))
             {
-                acc.add_function(ctx, func, None);
+                match deref_steps(&deref_chain, ty) {
+                    Some(steps) if steps > 0 => {
+                        mark::hit!(completes_methods_via_deref);
+                        let chain = deref_chain[..=steps]
+                            .iter()
+                            .map(|it| it.display(ctx.db).to_string())
+                            .sep_by(" → ")
+                            .to_string();
+                        acc.add_deref_method(ctx, func, chain)
+                    }
+                    _ => acc.add_function(ctx, func, None),
+                }
             }
             None::<()>
         });
     }
 }
 
+/// Checks that the trait of a trait method is implemented for `ty`, as
+/// method resolution may give up on proving that for the candidates.
+fn is_implemented_for(ctx: &CompletionContext, func: Function, ty: &Type) -> bool {
+    let trait_ = match func.as_assoc_item(ctx.db).map(|it| it.container(ctx.db)) {
+        Some(AssocItemContainer::Trait(it)) => it,
+        _ => return true,
+    };
+    // FIXME: we don't know the arguments of generic traits, besides `Self`.
+    if hir::GenericDef::from(trait_).params(ctx.db).len() > 1 {
+        return true;
+    }
+    ty.impls_trait(ctx.db, trait_, &[])
+}
+
+/// Exported traits of the current crate and its dependencies which can be
+/// imported into the current module and have a method starting with the typed
+/// text, like `Shout` for `S.sh<|>`.
+fn importable_traits(ctx: &CompletionContext) -> Vec<Trait> {
+    let typed = ctx.typed_text();
+    let module = match ctx.scope().module() {
+        // Every exported trait would be a candidate without a prefix of the
+        // method name.
+        Some(it) if !typed.is_empty() => it,
+        _ => return Vec::new(),
+    };
+    module
+        .krate()
+        .exported_traits_with_method(ctx.db, typed)
+        .into_iter()
+        .filter(|&it| module.find_use_path(ctx.db, ModuleDef::Trait(it)).is_some())
+        .collect()
+}
+
+/// How many `Deref` steps it takes to get from the receiver to `self_ty`.
+fn deref_steps(deref_chain: &[Type], mut self_ty: Type) -> Option<usize> {
+    while let Some(ty) = self_ty.remove_ref() {
+        self_ty = ty;
+    }
+    deref_chain.iter().position(|it| it == &self_ty)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
+    use expect::{expect, Expect};
     use insta::assert_debug_snapshot;
+    use test_utils::mark;
+
+    use crate::completion::{
+        test_utils::{
+            check_edit, check_edit_with_config, completion_list, completion_list_with_options,
+            do_completion, do_completion_with_options,
+        },
+        CompletionConfig, CompletionItem, CompletionKind,
+    };
 
     fn do_ref_completion(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Reference)
     }
 
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
     #[test]
     fn test_struct_field_completion() {
         assert_debug_snapshot!(
@@ -412,6 +539,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn skips_non_dispatchable_methods_of_trait_objects() {
+        mark::check!(skips_non_dispatchable_methods_of_trait_objects);
+        check(
+            r#"
+trait Shape {
+    fn area(&self) -> f64;
+    fn scale<T>(&self, by: T);
+    fn into_box(self) -> Box<Self> where Self: Sized;
+}
+fn foo(shape: &dyn Shape) {
+    shape.<|>
+}
+"#,
+            expect![[r#"
+                me area() fn area(&self) -> f64
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_fields_after_unwrap() {
+        check(
+            r#"
+//- /main.rs
+use core::option::Option;
+struct Person { name: u32, pub(crate) age: u8 }
+fn foo(person: Option<Person>) {
+    person.<|>
+}
+//- /core/lib.rs
+pub mod option { pub enum Option<T> { None, Some(T) } }
+"#,
+            expect![[r#"
+                fd unwrap().age u8 (after unwrap)
+                fd unwrap().name u32 (after unwrap)
+            "#]],
+        );
+        check_edit(
+            "name",
+            r#"
+//- /main.rs
+use core::result::Result;
+struct Person { name: u32 }
+fn foo(person: Result<Person, ()>) {
+    person.<|>
+}
+//- /core/lib.rs
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+"#,
+            r#"
+use core::result::Result;
+struct Person { name: u32 }
+fn foo(person: Result<Person, ()>) {
+    person.unwrap().name
+}
+"#,
+        );
+        check(
+            r#"
+enum Option<T> { None, Some(T) }
+struct Person { name: u32 }
+fn foo(person: Option<Person>) {
+    person.<|>
+}
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn skips_methods_requiring_qualified_sized_of_trait_objects() {
+        check(
+            r#"
+trait Shape {
+    fn area(&self) -> f64;
+    fn boxed(self) where Self: Sized + Clone;
+    fn cloned(&self) -> Self where Self: core::marker::Sized;
+}
+fn foo(shape: &dyn Shape) {
+    shape.<|>
+}
+"#,
+            expect![[r#"
+                me area() fn area(&self) -> f64
+            "#]],
+        );
+    }
+
+    #[test]
+    fn skips_methods_of_unimplemented_traits() {
+        check(
+            r#"
+trait Marker {}
+trait Ext { fn ext(&self) {} }
+impl<T: Marker> Ext for T {}
+trait Other { fn other(&self) {} }
+impl<T> Other for T {}
+struct NotMarked;
+fn foo(x: NotMarked) {
+    x.<|>
+}
+"#,
+            expect![[r#"
+                me other() fn other(&self)
+            "#]],
+        );
+    }
+
     #[test]
     fn test_trait_method_completion_deduplicated() {
         assert_debug_snapshot!(
@@ -559,6 +795,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn completes_tuple_fields_after_tuple_index() {
+        check(
+            r#"
+fn foo() {
+    let pair = ((1, 2.0), 3);
+    pair.0.<|>
+}
+"#,
+            expect![[r#"
+                fd 0 i32
+                fd 1 f64
+            "#]],
+        );
+        mark::check!(parenthesizes_tuple_index_receiver);
+        check_edit(
+            "1",
+            r#"
+fn foo() {
+    let pair = ((1, 2.0), 3);
+    pair.0.<|>
+}
+"#,
+            r#"
+fn foo() {
+    let pair = ((1, 2.0), 3);
+    (pair.0).1
+}
+"#,
+        );
+    }
+
     #[test]
     fn test_tuple_field_inference() {
         assert_debug_snapshot!(
@@ -869,4 +1137,206 @@ mod tests {
         "###
         );
     }
+
+    #[test]
+    fn completes_int_methods_on_ambiguous_float_literal() {
+        mark::check!(completes_int_methods_on_ambiguous_float_literal);
+        check(
+            r#"
+#[lang = "i32"]
+impl i32 { pub fn pow(self, exp: u32) -> i32 { self } }
+#[lang = "f64"]
+impl f64 { pub fn sqrt(self) -> f64 { self } }
+fn main() { 42.<|> }
+"#,
+            expect![[r#"
+                me pow(…) pub fn pow(self, exp: u32) -> i32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_methods_of_expected_int_type_on_ambiguous_float_literal() {
+        check(
+            r#"
+#[lang = "i32"]
+impl i32 { pub fn pow(self, exp: u32) -> i32 { self } }
+#[lang = "u64"]
+impl u64 { pub fn count_ones(self) -> u32 { 0 } }
+fn main() { let x: u64 = 42.<|> }
+"#,
+            expect![[r#"
+                me count_ones() pub fn count_ones(self) -> u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_methods_via_deref() {
+        mark::check!(completes_methods_via_deref);
+        check(
+            r#"
+#[lang = "deref"]
+trait Deref {
+    type Target;
+    fn deref(&self) -> &Self::Target;
+}
+struct Inner;
+impl Inner { fn inner_method(&self) {} }
+struct MyBox(Inner);
+impl Deref for MyBox {
+    type Target = Inner;
+    fn deref(&self) -> &Inner { &self.0 }
+}
+fn foo(b: &MyBox) { b.<|> }
+"#,
+            expect![[r#"
+                fd 0 Inner
+                me deref() fn deref(&self) -> &Self::Target
+                me inner_method() fn inner_method(&self) (via MyBox → Inner)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn recursive_deref_terminates() {
+        check(
+            r#"
+#[lang = "deref"]
+trait Deref {
+    type Target;
+    fn deref(&self) -> &Self::Target;
+}
+struct Loop;
+impl Loop { fn loop_method(&self) {} }
+impl Deref for Loop {
+    type Target = Loop;
+    fn deref(&self) -> &Loop { self }
+}
+fn foo(l: Loop) { l.<|> }
+"#,
+            expect![[r#"
+                me deref() fn deref(&self) -> &Self::Target
+                me loop_method() fn loop_method(&self)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_fields_of_call_results() {
+        check(
+            r#"
+struct Point { x: i32, y: i32 }
+impl Point { fn norm(&self) -> i32 { 0 } }
+fn make_point() -> Point { Point { x: 0, y: 0 } }
+fn main() { make_point().<|> }
+"#,
+            expect![[r#"
+                me norm() fn norm(&self) -> i32
+                fd x i32
+                fd y i32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_fields_of_generic_call_results() {
+        check(
+            r#"
+struct Wrapper<T> { inner: T, len: usize }
+fn wrap<T>() -> Wrapper<T> { loop {} }
+fn main() { wrap().<|> }
+"#,
+            expect![[r#"
+                fd inner {unknown}
+                fd len usize
+            "#]],
+        );
+    }
+
+    #[test]
+    fn imports_traits_of_methods() {
+        mark::check!(imports_traits_of_methods);
+        let fixture = r#"
+//- /main.rs
+struct S;
+fn main() { S.sh<|> }
+//- /ext/lib.rs
+pub trait Shout {
+    fn shout(&self);
+}
+impl<T> Shout for T {
+    fn shout(&self) {}
+}
+"#;
+        check(fixture, expect![[""]]);
+        check_edit_with_config(
+            "shout",
+            fixture,
+            r#"
+use ext::Shout;
+
+struct S;
+fn main() { S.shout()$0 }
+"#,
+            &CompletionConfig { enable_autoimport_completions: true, ..Default::default() },
+        );
+    }
+
+    #[test]
+    fn reports_imports_of_completions() {
+        let completions = do_completion_with_options(
+            r#"
+//- /main.rs
+struct S;
+impl S {
+    fn whisper(&self) {}
+}
+fn main() { S.sh<|> }
+//- /ext/lib.rs
+pub trait Shout {
+    fn shout(&self);
+}
+impl<T> Shout for T {
+    fn shout(&self) {}
+}
+pub trait Whistle {
+    fn whistle(&self);
+}
+impl<T> Whistle for T {
+    fn whistle(&self) {}
+}
+"#,
+            CompletionKind::Reference,
+            &CompletionConfig { enable_autoimport_completions: true, ..Default::default() },
+        );
+        let import = |label: &str| {
+            let item = completions.iter().find(|it| it.label() == label).unwrap();
+            item.import_edit().map(|it| it.import_path.clone())
+        };
+        assert_eq!(import("shout()").as_deref(), Some("ext::Shout"));
+        assert_eq!(import("whisper()"), None);
+        // Only traits with a method starting with the typed text are imported.
+        assert!(completions.iter().all(|it| it.label() != "whistle()"));
+    }
+
+    #[test]
+    fn no_trait_imports_for_unknown_receivers() {
+        let completions = completion_list_with_options(
+            r#"
+//- /main.rs
+fn main() { unknown().sh<|> }
+//- /ext/lib.rs
+pub trait Shout {
+    fn shout(&self);
+}
+impl<T> Shout for T {
+    fn shout(&self) {}
+}
+"#,
+            CompletionKind::Reference,
+            &CompletionConfig { enable_autoimport_completions: true, ..Default::default() },
+        );
+        assert_eq!(completions, "");
+    }
 }
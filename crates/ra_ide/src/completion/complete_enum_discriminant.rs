@@ -0,0 +1,115 @@
+//! Completes the next sequential value of an explicit enum discriminant.
+
+use ra_syntax::{
+    ast::{self, LiteralKind, PrefixOp},
+    AstNode,
+};
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+/// Offers `2` in `enum E { A = 1, B, C = <|> }`, which is the value the
+/// compiler would assign to `C` implicitly.
+pub(super) fn complete_enum_discriminant(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+) -> Option<()> {
+    let variant = ctx.enum_discriminant.as_ref()?;
+    let variant_list = variant.syntax().parent().and_then(ast::EnumVariantList::cast)?;
+
+    let mut next: i128 = 0;
+    for prev in variant_list.variants().take_while(|it| it != variant) {
+        let value = match prev.expr() {
+            Some(expr) => int_value(&expr)?,
+            None => next,
+        };
+        next = value.checked_add(1)?;
+    }
+
+    CompletionItem::new(CompletionKind::Magic, ctx.source_range(), next.to_string())
+        .kind(CompletionItemKind::Const)
+        .detail("next discriminant")
+        .add_to(acc);
+    Some(())
+}
+
+/// Evaluates integer literals, like `0x10` or `-1`.
+fn int_value(expr: &ast::Expr) -> Option<i128> {
+    match expr {
+        ast::Expr::Literal(lit) => {
+            let suffix = match lit.kind() {
+                LiteralKind::IntNumber { suffix } => suffix,
+                _ => return None,
+            };
+            let text = lit.token().text().replace('_', "");
+            let text = text.trim_end_matches(suffix.as_deref().unwrap_or(""));
+            let (radix, digits) = match text.get(..2) {
+                Some("0x") => (16, &text[2..]),
+                Some("0o") => (8, &text[2..]),
+                Some("0b") => (2, &text[2..]),
+                _ => (10, text),
+            };
+            i128::from_str_radix(digits, radix).ok()
+        }
+        ast::Expr::PrefixExpr(prefix) if prefix.op_kind() == Some(PrefixOp::Neg) => {
+            int_value(&prefix.expr()?).map(|it| -it)
+        }
+        ast::Expr::ParenExpr(paren) => int_value(&paren.expr()?),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{test_utils::completion_list, CompletionKind};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_next_discriminant() {
+        check(
+            r#"
+enum Flags { A, B = 0x10, C, D = <|> }
+"#,
+            expect![[r#"
+                ct 18 next discriminant
+            "#]],
+        );
+        check(
+            r#"
+enum Ordering { Less = -1, Equal = <|> }
+"#,
+            expect![[r#"
+                ct 0 next discriminant
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_next_discriminant_after_non_literal() {
+        check(
+            r#"
+const BASE: u32 = 8;
+enum E { A = BASE, B = <|> }
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn no_next_discriminant_on_overflow() {
+        check(
+            r#"
+#[repr(i128)]
+enum E { A = 170141183460469231731687303715884105727, B = <|> }
+"#,
+            expect![[""]],
+        );
+    }
+}
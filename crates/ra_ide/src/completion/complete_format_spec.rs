@@ -0,0 +1,233 @@
+//! Completes format specifiers, like `{:?}`, inside the format string of
+//! `format!`-like macros.
+
+use hir::Documentation;
+use ra_syntax::{ast, AstNode, AstToken, SyntaxKind, TextRange, TextSize};
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+pub(super) fn complete_format_spec(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let string = ast::String::cast(ctx.original_token.clone())?;
+    if !is_format_string(&string) {
+        return None;
+    }
+
+    let text = string.text().as_str();
+    let cursor = usize::from(ctx.offset - string.syntax().text_range().start());
+    let spec_start = open_placeholder(text.get(..cursor)?)?;
+    // Replace what the user has typed after the `:` already, like `{x:<|>}`.
+    let source_range = match text[spec_start..cursor].find(':') {
+        Some(colon) => {
+            let colon =
+                string.syntax().text_range().start() + TextSize::from((spec_start + colon) as u32);
+            TextRange::new(colon, ctx.offset)
+        }
+        None => TextRange::empty(ctx.offset),
+    };
+
+    for spec in FORMAT_SPECS {
+        let mut item = CompletionItem::new(CompletionKind::Magic, source_range, spec.label)
+            .kind(CompletionItemKind::Snippet)
+            .detail(spec.detail)
+            .documentation(Documentation::new(spec.doc));
+        match (spec.snippet, ctx.config.snippet_cap) {
+            (Some(snippet), Some(cap)) => item = item.insert_snippet(cap, snippet),
+            (Some(_), None) => continue,
+            (None, _) => {}
+        }
+        item.add_to(acc);
+    }
+    Some(())
+}
+
+/// Checks that `string` is the format string of a well-known formatting macro.
+///
+/// FIXME: this is purely syntactic; it should look at the macro expansion for
+/// `format_args!`, like syntax highlighting does.
+fn is_format_string(string: &ast::String) -> bool {
+    let token_tree = string.syntax().parent();
+    if token_tree.kind() != SyntaxKind::TOKEN_TREE {
+        return false;
+    }
+    let macro_name = token_tree
+        .parent()
+        .and_then(ast::MacroCall::cast)
+        .and_then(|it| it.path())
+        .and_then(|it| it.segment())
+        .and_then(|it| it.name_ref());
+    let is_format_macro = match macro_name {
+        Some(name) => FORMAT_MACROS.contains(&name.text().as_str()),
+        None => false,
+    };
+    // `write!(f, "...")` takes the format string after the writer, so just
+    // look for the first string literal.
+    let first_string = token_tree
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| it.kind() == SyntaxKind::STRING);
+    is_format_macro && first_string.as_ref() == Some(string.syntax())
+}
+
+/// Returns the offset just after the `{` of the placeholder which is still
+/// open at the end of `text`, if any.
+fn open_placeholder(text: &str) -> Option<usize> {
+    let mut open = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' if open.is_none() => {
+                // `{{` is an escaped brace.
+                if chars.peek().map(|&(_, c)| c) == Some('{') {
+                    chars.next();
+                } else {
+                    open = Some(idx + 1);
+                }
+            }
+            '}' => open = None,
+            _ => {}
+        }
+    }
+    open
+}
+
+const FORMAT_MACROS: &[&str] = &[
+    "format",
+    "format_args",
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "write",
+    "writeln",
+    "panic",
+    "unreachable",
+    "todo",
+    "unimplemented",
+];
+
+struct FormatSpec {
+    label: &'static str,
+    snippet: Option<&'static str>,
+    detail: &'static str,
+    doc: &'static str,
+}
+
+const fn spec(label: &'static str, detail: &'static str, doc: &'static str) -> FormatSpec {
+    FormatSpec { label, snippet: None, detail, doc }
+}
+
+const FORMAT_SPECS: &[FormatSpec] = &[
+    spec(":?", "Debug", "Formats the argument with its `Debug` implementation."),
+    spec(":#?", "Debug, pretty-printed", "Formats the argument with `Debug`, one field per line."),
+    spec(":x", "LowerHex", "Formats an integer as lower-case hexadecimal."),
+    spec(":X", "UpperHex", "Formats an integer as upper-case hexadecimal."),
+    spec(":#x", "LowerHex, with prefix", "Formats an integer as hexadecimal with a `0x` prefix."),
+    spec(":b", "Binary", "Formats an integer in binary."),
+    spec(":o", "Octal", "Formats an integer in octal."),
+    spec(":e", "LowerExp", "Formats a number in scientific notation."),
+    FormatSpec {
+        label: ":width",
+        snippet: Some(":${1:width}"),
+        detail: "minimum width",
+        doc: "Pads the argument to at least `width` characters. \
+              Use `width$` to take the width from a named argument.",
+    },
+    FormatSpec {
+        label: ":.precision",
+        snippet: Some(":.${1:precision}"),
+        detail: "precision",
+        doc: "Limits floating point numbers to `precision` decimal places, \
+              and truncates strings to `precision` characters.",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_format_specs() {
+        check(
+            r#"
+fn main() {
+    let x = 92;
+    format!("{<|>}", x);
+}
+"#,
+            expect![[r##"
+                sn :#? Debug, pretty-printed
+                sn :#x LowerHex, with prefix
+                sn :.precision precision
+                sn :? Debug
+                sn :X UpperHex
+                sn :b Binary
+                sn :e LowerExp
+                sn :o Octal
+                sn :width minimum width
+                sn :x LowerHex
+            "##]],
+        );
+    }
+
+    #[test]
+    fn completes_format_spec_after_argument_name() {
+        check_edit(
+            ":?",
+            r#"
+fn main() {
+    let x = 92;
+    format!("value: {x<|>}", x = x);
+}
+"#,
+            r#"
+fn main() {
+    let x = 92;
+    format!("value: {x:?}", x = x);
+}
+"#,
+        );
+        check_edit(
+            ":#?",
+            r#"
+fn main() {
+    let x = 92;
+    println!("{:<|>}", x);
+}
+"#,
+            r#"
+fn main() {
+    let x = 92;
+    println!("{:#?}", x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_format_specs_outside_placeholders() {
+        check(
+            r#"
+fn main() { format!("{{<|>", x); }
+"#,
+            expect![[r#""#]],
+        );
+        check(
+            r#"
+fn main() { let s = "{<|>}"; }
+"#,
+            expect![[r#""#]],
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! FIXME: write short doc here
 
-use ra_syntax::{ast, SyntaxKind};
+use hir::ScopeDef;
+use ra_syntax::{ast, AstNode, SyntaxKind};
 use test_utils::mark;
 
 use crate::completion::{
@@ -37,6 +38,36 @@ pub(super) fn complete_use_tree_keyword(acc: &mut Completions, ctx: &CompletionC
     }
 }
 
+/// Completes the `self::`, `super::` and `crate::` roots at the start of a
+/// path outside of `use` items. The editor completes again after inserting
+/// one, to list the items of that module.
+pub(super) fn complete_path_root_keyword(acc: &mut Completions, ctx: &CompletionContext) {
+    if !ctx.is_trivial_path
+        || ctx.use_item_syntax.is_some()
+        || ctx.record_lit_syntax.is_some()
+        || ctx.has_item_list_or_source_file_parent
+        || ctx.unsafe_is_prev
+    {
+        return;
+    }
+    let module = match ctx.scope().module() {
+        Some(it) => it,
+        None => return,
+    };
+    let mut roots = vec!["self::"];
+    if module.parent(ctx.db).is_some() {
+        roots.push("super::");
+    }
+    roots.push("crate::");
+    for root in roots {
+        CompletionItem::new(CompletionKind::Keyword, ctx.source_range(), root)
+            .kind(CompletionItemKind::Module)
+            .insert_text(root)
+            .trigger_completion()
+            .add_to(acc);
+    }
+}
+
 pub(super) fn complete_expr_keyword(acc: &mut Completions, ctx: &CompletionContext) {
     if ctx.token.kind() == SyntaxKind::COMMENT {
         mark::hit!(no_keyword_completion_in_comments);
@@ -134,6 +165,20 @@ pub(super) fn complete_expr_keyword(acc: &mut Completions, ctx: &CompletionConte
     if ctx.has_item_list_or_source_file_parent && !ctx.has_trait_parent {
         add_keyword(ctx, acc, "pub", "pub ")
     }
+    if ctx.is_path_type && ctx.is_trivial_path && !ctx.has_type_args && !ctx.is_type_bound {
+        for &(prefix, text) in TYPE_PREFIXES {
+            // Offering `&&` right after `&` is just noise.
+            if ctx.ref_type_amp_is_prev && prefix.starts_with('&') {
+                continue;
+            }
+            acc.add(
+                CompletionItem::new(CompletionKind::Keyword, ctx.source_range(), prefix)
+                    .kind(CompletionItemKind::Keyword)
+                    .insert_text(text)
+                    .trigger_completion(),
+            );
+        }
+    }
 
     if !ctx.is_trivial_path {
         return;
@@ -143,8 +188,17 @@ pub(super) fn complete_expr_keyword(acc: &mut Completions, ctx: &CompletionConte
         None => return,
     };
     acc.add_all(complete_return(ctx, &fn_def, ctx.can_be_stmt));
+    if ctx.can_be_stmt && fn_def.ret_type().is_some() {
+        complete_return_locals(acc, ctx);
+    }
 }
 
+/// Prefixes which start a type, offered at the start of type positions. The
+/// editor completes again after inserting one, so that the rest of the type
+/// can be picked right away.
+const TYPE_PREFIXES: &[(&str, &str)] =
+    &[("dyn", "dyn "), ("impl", "impl "), ("&", "&"), ("&mut", "&mut ")];
+
 fn keyword(ctx: &CompletionContext, kw: &str, snippet: &str) -> CompletionItem {
     let res = CompletionItem::new(CompletionKind::Keyword, ctx.source_range(), kw)
         .kind(CompletionItemKind::Keyword);
@@ -174,6 +228,28 @@ fn complete_return(
     Some(keyword(ctx, "return", snip))
 }
 
+/// Offers `return x;` for the locals `x` whose type is the return type of the
+/// function.
+fn complete_return_locals(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let fn_def =
+        ctx.sema.ancestors_with_macros(ctx.original_token.parent()).find_map(ast::FnDef::cast)?;
+    let ret_ty = ctx.sema.to_def(&fn_def)?.ret_type(ctx.db);
+    if ret_ty.is_unknown() {
+        return None;
+    }
+    ctx.scope().process_all_names(&mut |name, def| {
+        if let ScopeDef::Local(local) = def {
+            let ty = local.ty(ctx.db);
+            if !ty.is_unknown() && ty.could_unify_with(&ret_ty) {
+                let label = format!("return {}", name);
+                let snippet = format!("{};", label);
+                add_keyword(ctx, acc, &label, &snippet);
+            }
+        }
+    });
+    Some(())
+}
+
 #[cfg(test)]
 mod tests {
     use expect::{expect, Expect};
@@ -246,6 +322,7 @@ mod tests {
             r"fn quux() { <|> }",
             expect![[r#"
                 kw const
+                md crate::
                 kw extern
                 kw fn
                 kw if
@@ -256,6 +333,7 @@ mod tests {
                 kw match
                 kw mod
                 kw return
+                md self::
                 kw static
                 kw trait
                 kw type
@@ -272,6 +350,7 @@ mod tests {
             r"fn quux() { if true { <|> } }",
             expect![[r#"
                 kw const
+                md crate::
                 kw extern
                 kw fn
                 kw if
@@ -282,6 +361,7 @@ mod tests {
                 kw match
                 kw mod
                 kw return
+                md self::
                 kw static
                 kw trait
                 kw type
@@ -298,6 +378,7 @@ mod tests {
             r#"fn quux() { if true { () } <|> }"#,
             expect![[r#"
                 kw const
+                md crate::
                 kw else
                 kw else if
                 kw extern
@@ -310,6 +391,7 @@ mod tests {
                 kw match
                 kw mod
                 kw return
+                md self::
                 kw static
                 kw trait
                 kw type
@@ -336,11 +418,13 @@ fn quux() -> i32 {
 }
 "#,
             expect![[r#"
+                md crate::
                 kw if
                 kw if let
                 kw loop
                 kw match
                 kw return
+                md self::
                 kw unsafe
             "#]],
         );
@@ -381,6 +465,7 @@ fn quux() -> i32 {
                 kw break
                 kw const
                 kw continue
+                md crate::
                 kw extern
                 kw fn
                 kw if
@@ -391,6 +476,7 @@ fn quux() -> i32 {
                 kw match
                 kw mod
                 kw return
+                md self::
                 kw static
                 kw trait
                 kw type
@@ -463,6 +549,129 @@ fn quux() -> i32 {
         );
     }
 
+    #[test]
+    fn test_return_with_local_of_return_type() {
+        check(
+            r"
+fn parse(text: &str) -> u32 {
+    let value: u32 = 92;
+    let name = text;
+    re<|>
+}
+",
+            expect![[r#"
+                kw const
+                md crate::
+                kw extern
+                kw fn
+                kw if
+                kw if let
+                kw impl
+                kw let
+                kw loop
+                kw match
+                kw mod
+                kw return
+                kw return value
+                md self::
+                kw static
+                kw trait
+                kw type
+                kw unsafe
+                kw use
+                kw while
+            "#]],
+        );
+        check_edit(
+            "return value",
+            r"
+fn parse(text: &str) -> u32 {
+    let value: u32 = 92;
+    re<|>
+}
+",
+            r"
+fn parse(text: &str) -> u32 {
+    let value: u32 = 92;
+    return value;
+}
+",
+        );
+    }
+
+    #[test]
+    fn test_path_roots_at_path_start() {
+        check(
+            r"
+mod shapes {
+    fn area(shape: <|>) {}
+}
+",
+            expect![[r#"
+                kw &
+                kw &mut
+                md crate::
+                kw dyn
+                kw impl
+                kw return
+                md self::
+                md super::
+            "#]],
+        );
+        check_edit("crate::", r"fn main() { let _: <|> }", r"fn main() { let _: crate:: }");
+    }
+
+    #[test]
+    fn test_path_roots_in_bounds() {
+        check(
+            r"
+mod shapes {
+    fn area<T>(shape: T) where T: Clone + <|> {}
+}
+",
+            expect![[r#"
+                md crate::
+                kw return
+                md self::
+                md super::
+            "#]],
+        );
+        check(
+            r"fn my_fn(shape: &dyn Clone + <|>) {}",
+            expect![[r#"
+                md crate::
+                kw return
+                md self::
+            "#]],
+        );
+        check_edit("crate::", r"fn my_fn<T: <|>>() {}", r"fn my_fn<T: crate::>() {}");
+    }
+
+    #[test]
+    fn test_type_prefixes_in_type_position() {
+        check(
+            r"fn my_fn(shape: <|>) {}",
+            expect![[r#"
+                kw &
+                kw &mut
+                md crate::
+                kw dyn
+                kw impl
+                kw return
+                md self::
+            "#]],
+        );
+        check(
+            r"fn my_fn<T: <|>>() {}",
+            expect![[r#"
+                md crate::
+                kw return
+                md self::
+            "#]],
+        );
+        check_edit("dyn", r"fn my_fn(shape: &<|>) {}", r"fn my_fn(shape: &dyn ) {}");
+    }
+
     #[test]
     fn no_keyword_completion_in_comments() {
         mark::check!(no_keyword_completion_in_comments);
@@ -0,0 +1,122 @@
+//! Completes lifetimes right after `&` in type position and in trait object
+//! bounds.
+
+use ra_syntax::{
+    ast::{self, TypeParamsOwner},
+    match_ast, AstNode, TextRange,
+};
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+/// Completes `'static` and the lifetime parameters in scope, like in
+/// `fn foo<'a>(x: &<|> str)` or `Box<dyn Trait + <|>>`.
+pub(super) fn complete_lifetime(acc: &mut Completions, ctx: &CompletionContext) {
+    if !ctx.ref_type_amp_is_prev && !ctx.is_dyn_trait_extra_bound {
+        return;
+    }
+
+    // The user might have started typing the lifetime already, like `&'a<|>`.
+    let source_range = if ctx.original_token.text().starts_with('\'') {
+        TextRange::new(ctx.original_token.text_range().start(), ctx.offset)
+    } else {
+        ctx.source_range()
+    };
+
+    let mut lifetimes = Vec::new();
+    for node in ctx.token.parent().ancestors() {
+        let type_params = match_ast! {
+            match node {
+                ast::FnDef(it) => it.type_param_list(),
+                ast::ImplDef(it) => it.type_param_list(),
+                ast::TraitDef(it) => it.type_param_list(),
+                ast::StructDef(it) => it.type_param_list(),
+                ast::EnumDef(it) => it.type_param_list(),
+                ast::UnionDef(it) => it.type_param_list(),
+                ast::TypeAliasDef(it) => it.type_param_list(),
+                _ => None,
+            }
+        };
+        let params = type_params.into_iter().flat_map(|it| it.lifetime_params());
+        lifetimes.extend(params.filter_map(|it| it.lifetime_token()).map(|it| it.to_string()));
+    }
+    lifetimes.push("'static".to_string());
+
+    for lifetime in lifetimes {
+        CompletionItem::new(CompletionKind::Reference, source_range, lifetime)
+            .kind(CompletionItemKind::TypeParam)
+            .add_to(acc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_lifetimes_after_amp() {
+        check(
+            r#"
+struct S<'s>(&'s str);
+impl<'i> S<'i> {
+    fn foo<'a>(&self, x: &<|> str) {}
+}
+"#,
+            expect![[r#"
+                tp 'a
+                tp 'i
+                tp 'static
+                st S
+                tp Self
+                bn self &S
+                bn x &str
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_started_lifetime() {
+        check_edit(
+            "'static",
+            r#"
+fn foo(x: &'st<|> str) {}
+"#,
+            r#"
+fn foo(x: &'static str) {}
+"#,
+        );
+        check_edit(
+            "'a",
+            r#"
+fn foo<'a>(x: &'<|>) {}
+"#,
+            r#"
+fn foo<'a>(x: &'a) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_lifetimes_after_amp_in_expr() {
+        check(
+            r#"
+fn foo<'a>(x: &'a str) { let _ = &<|> }
+"#,
+            expect![[r#"
+                fn foo(…) fn foo<'a>(x: &'a str)
+                bn x &str
+            "#]],
+        );
+    }
+}
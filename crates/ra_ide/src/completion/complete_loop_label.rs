@@ -0,0 +1,90 @@
+//! Completes labels of the enclosing loops after `break` and `continue`.
+
+use ra_syntax::TextRange;
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+/// Offers `'outer` in `'outer: loop { loop { break <|> } }`.
+pub(super) fn complete_loop_label(acc: &mut Completions, ctx: &CompletionContext) {
+    // The user might have started typing the label already, like `break 'o<|>`.
+    let source_range = if ctx.original_token.text().starts_with('\'') {
+        TextRange::new(ctx.original_token.text_range().start(), ctx.offset)
+    } else {
+        ctx.source_range()
+    };
+
+    for label in &ctx.loop_labels {
+        CompletionItem::new(CompletionKind::Reference, source_range, label.clone())
+            .kind(CompletionItemKind::Binding)
+            .detail("loop label")
+            .add_to(acc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_loop_labels_after_break() {
+        check(
+            r#"
+fn main() {
+    'outer: loop {
+        'inner: while true {
+            break <|>
+        }
+    }
+}
+"#,
+            expect![[r#"
+                bn 'inner loop label
+                bn 'outer loop label
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_started_loop_label() {
+        check_edit(
+            "'outer",
+            r#"
+fn main() {
+    'outer: for _ in 0..10 { continue 'o<|> }
+}
+"#,
+            r#"
+fn main() {
+    'outer: for _ in 0..10 { continue 'outer }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_loop_labels_outside_closure() {
+        check(
+            r#"
+fn main() {
+    'outer: loop { let f = || { loop { break <|> } }; }
+}
+"#,
+            expect![[r#"
+                fn main() fn main()
+            "#]],
+        );
+    }
+}
@@ -88,8 +88,9 @@ mod tests {
                 kind: Macro,
                 detail: "macro_rules! vec",
                 documentation: Documentation(
-                    "Creates a [`Vec`] containing the arguments.\n\n- Create a [`Vec`] containing a given list of elements:\n\n```\nlet v = vec![1, 2, 3];\nassert_eq!(v[0], 1);\nassert_eq!(v[1], 2);\nassert_eq!(v[2], 3);\n```",
+                    "Creates a [`Vec`] containing the arguments.",
                 ),
+                has_more_documentation: true,
             },
         ]
         "###
@@ -125,8 +126,9 @@ mod tests {
                 kind: Macro,
                 detail: "macro_rules! foo",
                 documentation: Documentation(
-                    "Foo\n\nNot call `fooo!()` `fooo!()`, or `_foo![]` `_foo![]`.\nCall as `let _=foo!  { hello world };`",
+                    "Foo",
                 ),
+                has_more_documentation: true,
             },
             CompletionItem {
                 label: "main()",
@@ -0,0 +1,387 @@
+//! Completes patterns for the variants of an enum: all the arms of a `match`
+//! at once, a wildcard arm, or the pattern argument of `matches!`.
+
+use hir::{Adt, EnumVariant, Module, ModuleDef, ScopeDef, StructKind};
+use ra_syntax::{
+    ast::{self, edit::IndentLevel, NameOwner},
+    AstNode, SyntaxKind, SyntaxNode, SyntaxToken, TextRange, T,
+};
+use rustc_hash::FxHashSet;
+use stdx::{to_lower_snake_case, SepBy};
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+/// Offers to fill `match e { <|> }` with an arm for each variant of `e`.
+pub(super) fn complete_match_arms(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let cap = ctx.config.snippet_cap?;
+    let arm_list = ctx.token.parent().ancestors().find_map(ast::MatchArmList::cast)?;
+    // Only offer this in an empty match, or while typing the first pattern.
+    let is_empty = arm_list.arms().all(|arm| {
+        arm.fat_arrow_token().is_none() && arm.syntax().text_range().contains_inclusive(ctx.offset)
+    });
+    if !is_empty {
+        return None;
+    }
+    let match_expr = arm_list.syntax().parent().and_then(ast::MatchExpr::cast)?;
+    let enum_ = match ctx.sema.type_of_expr(&match_expr.expr()?)?.as_adt()? {
+        Adt::Enum(it) => it,
+        _ => return None,
+    };
+    let module = ctx.scope().module()?;
+
+    let indent = IndentLevel::from_node(match_expr.syntax()) + 1;
+    let mut arms = Vec::new();
+    for (idx, variant) in enum_.variants(ctx.db).into_iter().enumerate() {
+        let pat = variant_pat(ctx, module, variant, true)?;
+        arms.push(format!("{} => ${{{}:todo!()}},", pat, idx + 1));
+    }
+    if arms.is_empty() {
+        return None;
+    }
+
+    let snippet = arms.into_iter().sep_by(&format!("\n{}", indent)).to_string();
+    CompletionItem::new(CompletionKind::Magic, ctx.source_range(), "fill match arms")
+        .kind(CompletionItemKind::Snippet)
+        .insert_snippet(cap, snippet)
+        .add_to(acc);
+    Some(())
+}
+
+/// Offers a wildcard arm, `_ => `, while typing the pattern of a new arm of a
+/// `match` whose arms don't cover all cases yet.
+pub(super) fn complete_wildcard_arm(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let cap = ctx.config.snippet_cap?;
+    let arm_list = ctx.token.parent().ancestors().find_map(ast::MatchArmList::cast)?;
+    // Only at the top of the pattern, not nested in one like `Some(<|>)`.
+    let is_nested = ctx
+        .token
+        .parent()
+        .ancestors()
+        .take_while(|it| it.kind() != SyntaxKind::MATCH_ARM && it != arm_list.syntax())
+        .any(|it| ast::Pat::can_cast(it.kind()) && !is_catch_all(&it));
+    if is_nested {
+        return None;
+    }
+    let (typed, others): (Vec<ast::MatchArm>, Vec<ast::MatchArm>) =
+        arm_list.arms().partition(|it| it.syntax().text_range().contains_inclusive(ctx.offset));
+    if typed.iter().any(|it| it.fat_arrow_token().is_some()) {
+        return None;
+    }
+
+    let match_expr = arm_list.syntax().parent().and_then(ast::MatchExpr::cast)?;
+    let variants = match ctx.sema.type_of_expr(&match_expr.expr()?)?.as_adt() {
+        Some(Adt::Enum(it)) => it.variants(ctx.db),
+        _ => Vec::new(),
+    };
+    let variant_names: Vec<String> =
+        variants.iter().map(|it| it.name(ctx.db).to_string()).collect();
+    let mut covered = FxHashSet::default();
+    for pat in others.iter().filter(|it| it.guard().is_none()).filter_map(|it| it.pat()) {
+        let alternatives = match pat {
+            ast::Pat::OrPat(it) => it.pats().collect(),
+            it => vec![it],
+        };
+        for pat in alternatives {
+            let path = match &pat {
+                ast::Pat::BindPat(it) if it.pat().is_none() => {
+                    let name = it.name()?.text().to_string();
+                    if !variant_names.contains(&name) {
+                        return None;
+                    }
+                    covered.insert(name);
+                    continue;
+                }
+                ast::Pat::PlaceholderPat(_) => return None,
+                ast::Pat::PathPat(it) => it.path(),
+                ast::Pat::TupleStructPat(it) => it.path(),
+                ast::Pat::RecordPat(it) => it.path(),
+                _ => None,
+            };
+            let has_refutable_fields = pat
+                .syntax()
+                .descendants()
+                .skip(1)
+                .any(|it| ast::Pat::can_cast(it.kind()) && !is_catch_all(&it));
+            if let Some(segment) = path.and_then(|it| it.segment()) {
+                if !has_refutable_fields {
+                    covered.insert(segment.syntax().text().to_string());
+                }
+            }
+        }
+    }
+    if !variant_names.is_empty() && variant_names.iter().all(|it| covered.contains(it)) {
+        return None;
+    }
+
+    // `_` is not an identifier, so it is not part of `source_range`.
+    let source_range = if ctx.original_token.kind() == T![_] {
+        TextRange::new(ctx.original_token.text_range().start(), ctx.offset)
+    } else {
+        ctx.source_range()
+    };
+    CompletionItem::new(CompletionKind::Magic, source_range, "_ =>")
+        .kind(CompletionItemKind::Snippet)
+        .lookup_by("_")
+        .insert_snippet(cap, "_ => $0")
+        .add_to(acc);
+    Some(())
+}
+
+/// Whether the pattern `node` matches anything, like `_` or `x`.
+fn is_catch_all(node: &SyntaxNode) -> bool {
+    match ast::Pat::cast(node.clone()) {
+        Some(ast::Pat::PlaceholderPat(_)) | Some(ast::Pat::DotDotPat(_)) => true,
+        Some(ast::Pat::BindPat(it)) => it.pat().is_none(),
+        _ => false,
+    }
+}
+
+/// Offers a pattern for each variant as the second argument of
+/// `matches!(e, <|>)`, or of `assert_matches!`.
+pub(super) fn complete_matches_macro_pattern(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+) -> Option<()> {
+    let token_tree = ctx.token.parent();
+    let macro_name = token_tree
+        .parent()
+        .and_then(ast::MacroCall::cast)
+        .and_then(|it| it.path())
+        .and_then(|it| it.segment())
+        .and_then(|it| it.name_ref())?;
+    if !MATCHES_MACROS.contains(&macro_name.text().as_str()) {
+        return None;
+    }
+
+    // The pattern is the second argument, so there must be exactly one
+    // comma before the cursor.
+    let args: Vec<SyntaxToken> = token_tree
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .take_while(|it| it != &ctx.token)
+        .filter(|it| !it.kind().is_trivia() && it.kind() != T!['('])
+        .collect();
+    let scrutinee = match args.as_slice() {
+        [scrutinee, comma] if comma.kind() == T![,] => scrutinee,
+        _ => return None,
+    };
+
+    // FIXME: this only handles a local as the scrutinee, it should get the
+    // type of an arbitrary expression from the macro expansion.
+    let mut ty = None;
+    ctx.scope().process_all_names(&mut |name, def| match def {
+        ScopeDef::Local(local) if name.to_string() == scrutinee.text().as_str() => {
+            ty = Some(local.ty(ctx.db))
+        }
+        _ => {}
+    });
+    let enum_ = match ty?.as_adt()? {
+        Adt::Enum(it) => it,
+        _ => return None,
+    };
+    let module = ctx.scope().module()?;
+    for variant in enum_.variants(ctx.db) {
+        let pat = variant_pat(ctx, module, variant, false)?;
+        CompletionItem::new(CompletionKind::Magic, ctx.source_range(), pat)
+            .kind(CompletionItemKind::EnumVariant)
+            .add_to(acc);
+    }
+    Some(())
+}
+
+const MATCHES_MACROS: &[&str] = &["matches", "assert_matches", "debug_assert_matches"];
+
+/// Renders a pattern which matches any value of `variant`, like
+/// `Shape::Rect { w, h }`. The fields of tuple variants are bound to names
+/// derived from the variant if `bind_tuple_fields` is set, and ignored with
+/// `_` otherwise.
+fn variant_pat(
+    ctx: &CompletionContext,
+    module: Module,
+    variant: EnumVariant,
+    bind_tuple_fields: bool,
+) -> Option<String> {
+    let path = module.find_use_path(ctx.db, ModuleDef::from(variant))?;
+    let fields = variant.fields(ctx.db);
+    let pat = match variant.kind(ctx.db) {
+        StructKind::Tuple if bind_tuple_fields => {
+            let name = to_lower_snake_case(&variant.name(ctx.db).to_string());
+            let bindings: Vec<String> = match fields.len() {
+                1 => vec![name],
+                n => (0..n).map(|idx| format!("{}{}", name, idx)).collect(),
+            };
+            let bindings = bindings.into_iter().map(|it| {
+                if SyntaxKind::from_keyword(&it).is_some() {
+                    format!("{}_", it)
+                } else {
+                    it
+                }
+            });
+            format!("{}({})", path, bindings.sep_by(", "))
+        }
+        StructKind::Tuple => format!("{}({})", path, fields.iter().map(|_| "_").sep_by(", ")),
+        StructKind::Record => {
+            format!("{} {{ {} }}", path, fields.iter().map(|it| it.name(ctx.db)).sep_by(", "))
+        }
+        StructKind::Unit => path.to_string(),
+    };
+    Some(pat)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn fills_match_arms() {
+        check_edit(
+            "fill match arms",
+            r#"
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Line(u32, u32), Type(u8), Empty }
+fn area(s: Shape) {
+    match s {
+        <|>
+    }
+}
+"#,
+            r#"
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Line(u32, u32), Type(u8), Empty }
+fn area(s: Shape) {
+    match s {
+        Shape::Circle(circle) => ${1:todo!()},
+        Shape::Rect { w, h } => ${2:todo!()},
+        Shape::Line(line0, line1) => ${3:todo!()},
+        Shape::Type(type_) => ${4:todo!()},
+        Shape::Empty => ${5:todo!()},
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_wildcard_arm() {
+        check_edit(
+            "_",
+            r#"
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Empty }
+fn area(s: Shape) -> f32 {
+    match s {
+        Shape::Circle(r) => r * r,
+        <|>
+    }
+}
+"#,
+            r#"
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Empty }
+fn area(s: Shape) -> f32 {
+    match s {
+        Shape::Circle(r) => r * r,
+        _ => $0
+    }
+}
+"#,
+        );
+        check_edit(
+            "_",
+            r#"
+fn describe(n: u32) -> &'static str {
+    match n {
+        0 => "zero",
+        _<|>
+    }
+}
+"#,
+            r#"
+fn describe(n: u32) -> &'static str {
+    match n {
+        0 => "zero",
+        _ => $0
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_wildcard_arm_in_exhaustive_matches() {
+        check(
+            r#"
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Empty }
+fn area(s: Shape) -> f32 {
+    match s {
+        Shape::Circle(_) | Shape::Empty => 1.0,
+        Shape::Rect { w, .. } => w as f32,
+        <|>
+    }
+}
+"#,
+            expect![[""]],
+        );
+        check(
+            r#"
+fn describe(n: u32) -> &'static str {
+    match n {
+        0 => "zero",
+        other => "many",
+        <|>
+    }
+}
+"#,
+            expect![[""]],
+        );
+        check(
+            r#"
+enum Shape { Circle(f32), Empty }
+fn area(s: Shape) -> f32 {
+    match s {
+        Shape::Circle(1.0) => 1.0,
+        Shape::Empty => 0.0,
+        Shape::Circle(<|>)
+    }
+}
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn completes_matches_macro_patterns() {
+        check(
+            r#"
+macro_rules! matches { ($e:expr, $p:pat) => {} }
+enum Shape { Circle(f32), Rect { w: u32, h: u32 }, Empty }
+fn is_round(s: Shape) -> bool {
+    matches!(s, <|>)
+}
+"#,
+            expect![[r#"
+                ev Shape::Circle(_)
+                ev Shape::Empty
+                ev Shape::Rect { w, h }
+            "#]],
+        );
+        check(
+            r#"
+macro_rules! matches { ($e:expr, $p:pat) => {} }
+enum Shape { Circle(f32), Empty }
+fn is_round(s: Shape) -> bool {
+    matches!(<|>)
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
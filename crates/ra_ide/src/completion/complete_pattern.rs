@@ -1,5 +1,9 @@
 //! FIXME: write short doc here
 
+use ra_syntax::{ast, AstNode};
+use rustc_hash::FxHashSet;
+use test_utils::mark;
+
 use crate::completion::{CompletionContext, Completions};
 
 /// Completes constats and paths in patterns.
@@ -11,9 +15,26 @@ pub(super) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
         return;
     }
 
+    let covered_variants = covered_variants(ctx);
+
     // FIXME: ideally, we should look at the type we are matching against and
     // suggest variants + auto-imports
     ctx.scope().process_all_names(&mut |name, res| {
+        if let hir::ScopeDef::ModuleDef(hir::ModuleDef::EnumVariant(variant)) = res {
+            if covered_variants.contains(&variant) {
+                mark::hit!(skips_variants_covered_by_other_arms);
+                return;
+            }
+        }
+        // Only constants can be range bounds.
+        let is_const_or_module = matches!(
+            res,
+            hir::ScopeDef::ModuleDef(hir::ModuleDef::Const(..))
+                | hir::ScopeDef::ModuleDef(hir::ModuleDef::Module(..))
+        );
+        if ctx.is_range_pat_end && !is_const_or_module {
+            return;
+        }
         match &res {
             hir::ScopeDef::ModuleDef(def) => match def {
                 hir::ModuleDef::Adt(hir::Adt::Enum(..))
@@ -31,10 +52,80 @@ pub(super) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
     });
 }
 
+/// The enum variants which other arms of the `match` we are completing an arm
+/// of match completely, like `Some` with `Some(_) => ()`.
+fn covered_variants(ctx: &CompletionContext) -> FxHashSet<hir::EnumVariant> {
+    let mut res = FxHashSet::default();
+    let arm_list = match ctx
+        .sema
+        .ancestors_with_macros(ctx.original_token.parent())
+        .find_map(ast::MatchArmList::cast)
+    {
+        Some(it) => it,
+        None => return res,
+    };
+    for arm in arm_list.arms() {
+        if arm.syntax().text_range().contains_inclusive(ctx.offset) || arm.guard().is_some() {
+            continue;
+        }
+        let pats = match arm.pat() {
+            Some(ast::Pat::OrPat(or_pat)) => or_pat.pats().collect(),
+            Some(pat) => vec![pat],
+            None => continue,
+        };
+        res.extend(pats.iter().filter_map(|pat| covered_variant(ctx, pat)));
+    }
+    res
+}
+
+fn covered_variant(ctx: &CompletionContext, pat: &ast::Pat) -> Option<hir::EnumVariant> {
+    let path = match pat {
+        ast::Pat::PathPat(it) => it.path()?,
+        ast::Pat::TupleStructPat(it) if it.args().all(|it| is_irrefutable(ctx, &it)) => {
+            it.path()?
+        }
+        ast::Pat::RecordPat(it) => {
+            let fields = it.record_field_pat_list()?;
+            let fields_are_irrefutable = fields
+                .record_field_pats()
+                .all(|it| it.pat().map_or(true, |it| is_irrefutable(ctx, &it)));
+            if !fields_are_irrefutable {
+                return None;
+            }
+            it.path()?
+        }
+        // Unit variants, like `None`, are parsed as bindings.
+        ast::Pat::BindPat(it) => {
+            return match ctx.sema.resolve_bind_pat_to_const(it)? {
+                hir::ModuleDef::EnumVariant(variant) => Some(variant),
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+    match ctx.sema.resolve_path(&path)? {
+        hir::PathResolution::Def(hir::ModuleDef::EnumVariant(variant)) => Some(variant),
+        _ => None,
+    }
+}
+
+fn is_irrefutable(ctx: &CompletionContext, pat: &ast::Pat) -> bool {
+    match pat {
+        ast::Pat::PlaceholderPat(_) | ast::Pat::DotDotPat(_) => true,
+        ast::Pat::BindPat(it) => {
+            it.pat().map_or(true, |it| is_irrefutable(ctx, &it))
+                && ctx.sema.resolve_bind_pat_to_const(it).is_none()
+        }
+        ast::Pat::TuplePat(it) => it.args().all(|it| is_irrefutable(ctx, &it)),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
     use insta::assert_debug_snapshot;
+    use test_utils::mark;
 
     fn complete(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Reference)
@@ -135,4 +226,58 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn completes_consts_in_range_pattern_end() {
+        mark::check!(boosts_consts_of_range_bound_type);
+        let completions = complete(
+            r#"
+            const MAX: u8 = 9;
+            const NAME: &str = "x";
+            enum E { X }
+            fn foo(x: u8) { match x { 1..=<|> => () } }
+            "#,
+        );
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, vec!["MAX", "NAME"]);
+        let boosted: Vec<&str> =
+            completions.iter().filter(|it| it.score().is_some()).map(|it| it.label()).collect();
+        assert_eq!(boosted, vec!["MAX"]);
+    }
+
+    #[test]
+    fn skips_variants_covered_by_other_arms() {
+        mark::check!(skips_variants_covered_by_other_arms);
+        let completions = complete(
+            r#"
+            enum Option<T> { None, Some(T) }
+            use Option::*;
+            fn foo(x: Option<u8>) {
+                match x {
+                    Some(_) => (),
+                    <|>
+                }
+            }
+            "#,
+        );
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, vec!["None", "Option"]);
+
+        // `Some(1)` leaves other values of `Some` unmatched.
+        let completions = complete(
+            r#"
+            enum Option<T> { None, Some(T) }
+            use Option::*;
+            fn foo(x: Option<u8>) {
+                match x {
+                    Some(1) => (),
+                    None if true => (),
+                    <|>
+                }
+            }
+            "#,
+        );
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, vec!["None", "Option", "Some(…)"]);
+    }
 }
@@ -46,6 +46,16 @@ pub(super) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionCon
                         }
                     }
                 }
+                if ctx.is_type_bound
+                    && !matches!(
+                        def,
+                        ScopeDef::ModuleDef(hir::ModuleDef::Trait(_))
+                            | ScopeDef::ModuleDef(hir::ModuleDef::Module(_))
+                    )
+                {
+                    mark::hit!(completes_only_traits_in_qualified_bounds);
+                    continue;
+                }
 
                 acc.add_resolution(ctx, name.to_string(), &def);
             }
@@ -102,10 +112,15 @@ pub(super) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionCon
                 if context_module.map_or(false, |m| !item.is_visible_from(ctx.db, m)) {
                     continue;
                 }
+                // `<S as Trait>::Assoc` in type position can only name a type.
+                if ctx.is_qualified_self_path
+                    && ctx.is_path_type
+                    && !matches!(item, hir::AssocItem::TypeAlias(_))
+                {
+                    continue;
+                }
                 match item {
-                    hir::AssocItem::Function(func) => {
-                        acc.add_function(ctx, func, None);
-                    }
+                    hir::AssocItem::Function(func) => acc.add_trait_function(ctx, func),
                     hir::AssocItem::Const(ct) => acc.add_const(ctx, ct),
                     hir::AssocItem::TypeAlias(ty) => acc.add_type_alias(ctx, ty),
                 }
@@ -147,15 +162,24 @@ pub(super) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionCon
 
 #[cfg(test)]
 mod tests {
+    use expect::{expect, Expect};
     use test_utils::mark;
 
-    use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
+    use crate::completion::{
+        test_utils::{completion_list, do_completion},
+        CompletionItem, CompletionKind,
+    };
     use insta::assert_debug_snapshot;
 
     fn do_reference_completion(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Reference)
     }
 
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
     #[test]
     fn dont_complete_current_use() {
         mark::check!(dont_complete_current_use);
@@ -175,6 +199,17 @@ mod tests {
         assert_eq!(completions.len(), 2);
     }
 
+    #[test]
+    fn dont_complete_items_in_use_rename() {
+        check(r"mod foo { pub struct Bar; } use foo::Bar as <|>;", expect![[""]]);
+        check(r"mod foo { pub struct Bar; } use foo::Bar as B<|>;", expect![[""]]);
+        check(r"mod foo { pub struct Bar; } use foo::{Bar as <|>};", expect![[""]]);
+        expect![[""]].assert_eq(&completion_list(
+            r"mod foo { pub struct Bar; } use foo::Bar as <|>;",
+            CompletionKind::Keyword,
+        ));
+    }
+
     #[test]
     fn dont_complete_primitive_in_use() {
         let completions = do_completion(r"use self::<|>;", CompletionKind::BuiltinType);
@@ -839,7 +874,7 @@ mod tests {
                 insert: "m()$0",
                 kind: Function,
                 lookup: "m",
-                detail: "fn m()",
+                detail: "fn m() (required)",
                 documentation: Documentation(
                     "A trait method",
                 ),
@@ -849,6 +884,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn completes_trait_associated_functions_with_status() {
+        check(
+            r#"
+trait MyTrait {
+    fn new() -> Self;
+    fn name(&self) -> String { String::new() }
+}
+
+fn foo() { MyTrait::<|> }
+"#,
+            expect![[r#"
+                me name(…) fn name(&self) -> String (default)
+                fn new() fn new() -> Self (required)
+            "#]],
+        );
+    }
+
     #[test]
     fn completes_trait_associated_method_2() {
         assert_debug_snapshot!(
@@ -921,6 +974,98 @@ mod tests {
         );
     }
 
+    #[test]
+    fn completes_assoc_items_of_qualified_self() {
+        check(
+            r#"
+trait Shape {
+    const SIDES: u32;
+    type Unit;
+    fn area(&self) -> f64;
+    fn unit() -> Self::Unit;
+}
+struct Square;
+impl Shape for Square {
+    const SIDES: u32 = 4;
+    type Unit = f64;
+    fn area(&self) -> f64 { 1.0 }
+    fn unit() -> f64 { 1.0 }
+}
+fn main() { let _ = <Square as Shape>::<|> }
+"#,
+            expect![[r#"
+                ct SIDES const SIDES: u32;
+                ta Unit type Unit; (from trait Shape)
+                me area(…) fn area(&self) -> f64
+                fn unit() fn unit() -> f64
+            "#]],
+        );
+        check(
+            r#"
+trait Shape {
+    const SIDES: u32;
+    type Unit;
+}
+struct Square;
+fn main() { let _: <Square as Shape>::<|>; }
+"#,
+            expect![[r#"
+                ta Unit type Unit; (from trait Shape)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn substitutes_self_type_in_qualified_assoc_fns() {
+        check(
+            r#"
+trait Shape {
+    type Unit;
+    fn new(size: Self::Unit) -> Self;
+    fn scaled(self, by: Self::Unit) -> Self;
+    fn boxed(self: Box<Self>) -> Option<Self::Unit>;
+}
+struct Box<T>(T);
+enum Option<T> { None, Some(T) }
+struct Square;
+impl Shape for Square {
+    type Unit = f64;
+    fn new(size: f64) -> Square { Square }
+    fn scaled(self, by: f64) -> Square { self }
+    fn boxed(self: Box<Self>) -> Option<f64> { Option::None }
+}
+fn main() { let _ = <Square as Shape>::<|> }
+"#,
+            expect![[r#"
+                ta Unit type Unit; (from trait Shape)
+                me boxed(…) fn boxed(self: Box<Square>) -> Option<f64>
+                fn new(…) fn new(size: f64) -> Square
+                me scaled(…) fn scaled(self, by: f64) -> Square
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs
+use shapes::{Shape, Square};
+fn main() { let _ = <Square as Shape>::<|> }
+//- /shapes/lib.rs
+pub trait Shape {
+    type Unit;
+    fn new(size: Self::Unit) -> Self;
+}
+pub struct Square;
+impl Shape for Square {
+    type Unit = u32;
+    fn new(size: u32) -> Square { Square }
+}
+"#,
+            expect![[r#"
+                ta Unit type Unit; (from trait Shape)
+                fn new(…) fn new(size: u32) -> Square
+            "#]],
+        );
+    }
+
     #[test]
     fn completes_ty_param_assoc_ty() {
         assert_debug_snapshot!(
@@ -970,7 +1115,7 @@ mod tests {
                 delete: 221..221,
                 insert: "SubTy",
                 kind: TypeAlias,
-                detail: "type SubTy;",
+                detail: "type SubTy; (from trait Sub)",
             },
             CompletionItem {
                 label: "Ty",
@@ -978,7 +1123,7 @@ mod tests {
                 delete: 221..221,
                 insert: "Ty",
                 kind: TypeAlias,
-                detail: "type Ty;",
+                detail: "type Ty; (from trait Super)",
             },
             CompletionItem {
                 label: "func()",
@@ -1021,6 +1166,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shows_trait_of_assoc_types() {
+        check(
+            r#"
+trait Iterator { type Item; }
+fn first<I: Iterator>(iter: I) -> I::<|>
+"#,
+            expect![[r#"
+                ta Item type Item; (from trait Iterator)
+            "#]],
+        );
+    }
+
     #[test]
     fn completes_self_param_assoc_ty() {
         assert_debug_snapshot!(
@@ -1075,7 +1233,7 @@ mod tests {
                 delete: 367..367,
                 insert: "SubTy",
                 kind: TypeAlias,
-                detail: "type SubTy;",
+                detail: "type SubTy; (from trait Sub)",
             },
             CompletionItem {
                 label: "Ty",
@@ -1083,7 +1241,7 @@ mod tests {
                 delete: 367..367,
                 insert: "Ty",
                 kind: TypeAlias,
-                detail: "type Ty;",
+                detail: "type Ty; (from trait Super)",
             },
             CompletionItem {
                 label: "func()",
@@ -1372,4 +1530,23 @@ mod tests {
             @r###"[]"###
         )
     }
+
+    #[test]
+    fn completes_only_traits_and_modules_in_qualified_bounds() {
+        mark::check!(completes_only_traits_in_qualified_bounds);
+        check(
+            r#"
+mod fmt {
+    pub trait Display {}
+    pub struct Formatter;
+    pub mod rt {}
+}
+fn f<T: fmt::<|>>() {}
+"#,
+            expect![[r#"
+                tt Display
+                md rt
+            "#]],
+        );
+    }
 }
@@ -1,4 +1,7 @@
 //! Complete fields in record literals and patterns.
+use hir::ScopeDef;
+use rustc_hash::FxHashMap;
+
 use crate::completion::{CompletionContext, Completions};
 
 pub(super) fn complete_record(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
@@ -9,8 +12,25 @@ pub(super) fn complete_record(acc: &mut Completions, ctx: &CompletionContext) ->
         (_, Some(record_lit)) => ctx.sema.record_literal_missing_fields(record_lit),
     };
 
+    // Bindings which can be used with the `Foo { field }` shorthand syntax.
+    let mut bindings = FxHashMap::default();
+    if ctx.record_lit_syntax.is_some() {
+        ctx.scope().process_all_names(&mut |name, res| {
+            if let ScopeDef::Local(local) = res {
+                bindings.entry(name.to_string()).or_insert_with(|| local.ty(ctx.db));
+            }
+        });
+    }
+
     for (field, ty) in missing_fields {
-        acc.add_field(ctx, field, &ty)
+        let has_binding = bindings
+            .get(&field.name(ctx.db).to_string())
+            .map_or(false, |it| it.could_unify_with(&ty));
+        if has_binding {
+            acc.add_field_shorthand(ctx, field, &ty)
+        } else {
+            acc.add_field(ctx, field, &ty)
+        }
     }
 
     Some(())
@@ -137,6 +157,24 @@ fn foo() {
         );
     }
 
+    #[test]
+    fn test_record_literal_enum_variant_missing_fields() {
+        check(
+            r#"
+enum Shape {
+    Circle { radius: f32 },
+    Rect { width: u32, height: u32 },
+}
+fn foo() {
+    let _ = Shape::Rect { width: 92, <|> }
+}
+"#,
+            expect![[r#"
+                fd height u32
+            "#]],
+        );
+    }
+
     #[test]
     fn test_record_literal_two_structs() {
         check(
@@ -207,6 +245,29 @@ fn main() {
         );
     }
 
+    #[test]
+    fn completes_field_shorthand_for_binding() {
+        check(
+            r#"
+mod metric { pub struct Meters; }
+mod imperial { pub struct Meters; }
+struct Rect { width: u32, height: u32, depth: metric::Meters }
+
+fn main() {
+    let width: u32 = 92;
+    let height = "not a number";
+    let depth = imperial::Meters;
+    let r = Rect { <|> }
+}
+"#,
+            expect![[r#"
+                fd depth Meters
+                fd height u32
+                fd width u32 (shorthand)
+            "#]],
+        );
+    }
+
     #[test]
     fn completes_functional_update() {
         check(
@@ -1,15 +1,8 @@
 //! FIXME: write short doc here
 
-use crate::completion::{
-    completion_config::SnippetCap, completion_item::Builder, CompletionContext, CompletionItem,
-    CompletionItemKind, CompletionKind, Completions,
-};
-
-fn snippet(ctx: &CompletionContext, cap: SnippetCap, label: &str, snippet: &str) -> Builder {
-    CompletionItem::new(CompletionKind::Snippet, ctx.source_range(), label)
-        .insert_snippet(cap, snippet)
-        .kind(CompletionItemKind::Snippet)
-}
+use stdx::SepBy;
+
+use crate::completion::{CompletionContext, Completions};
 
 pub(super) fn complete_expr_snippet(acc: &mut Completions, ctx: &CompletionContext) {
     if !(ctx.is_trivial_path && ctx.function_syntax.is_some()) {
@@ -20,8 +13,30 @@ pub(super) fn complete_expr_snippet(acc: &mut Completions, ctx: &CompletionConte
         None => return,
     };
 
-    snippet(ctx, cap, "pd", "eprintln!(\"$0 = {:?}\", $0);").add_to(acc);
-    snippet(ctx, cap, "ppd", "eprintln!(\"$0 = {:#?}\", $0);").add_to(acc);
+    acc.add_snippet(
+        ctx,
+        cap,
+        "pd",
+        None,
+        "eprintln!(\"$0 = {:?}\", $0);",
+        Some("Prints a value with its `Debug` implementation to stderr."),
+    );
+    acc.add_snippet(
+        ctx,
+        cap,
+        "ppd",
+        None,
+        "eprintln!(\"$0 = {:#?}\", $0);",
+        Some("Pretty-prints a value with its `Debug` implementation to stderr."),
+    );
+
+    if let Some(params) = &ctx.closure_param_names {
+        let label = format!("|{}| …", params.join(", "));
+        let placeholders =
+            params.iter().enumerate().map(|(idx, it)| format!("${{{}:{}}}", idx + 1, it));
+        let snippet = format!("|{}| $0", placeholders.sep_by(", "));
+        acc.add_snippet(ctx, cap, &label, None, &snippet, None);
+    }
 }
 
 pub(super) fn complete_item_snippet(acc: &mut Completions, ctx: &CompletionContext) {
@@ -33,10 +48,11 @@ pub(super) fn complete_item_snippet(acc: &mut Completions, ctx: &CompletionConte
         None => return,
     };
 
-    snippet(
+    acc.add_snippet(
         ctx,
         cap,
         "Test module",
+        Some("tmod"),
         "\
 #[cfg(test)]
 mod tests {
@@ -47,30 +63,39 @@ mod tests {
         $0
     }
 }",
-    )
-    .lookup_by("tmod")
-    .add_to(acc);
+        None,
+    );
 
-    snippet(
+    acc.add_snippet(
         ctx,
         cap,
         "Test function",
+        Some("tfn"),
         "\
 #[test]
 fn ${1:feature}() {
     $0
 }",
-    )
-    .lookup_by("tfn")
-    .add_to(acc);
+        None,
+    );
 
-    snippet(ctx, cap, "macro_rules", "macro_rules! $1 {\n\t($2) => {\n\t\t$0\n\t};\n}").add_to(acc);
-    snippet(ctx, cap, "pub(crate)", "pub(crate) $0").add_to(acc);
+    acc.add_snippet(
+        ctx,
+        cap,
+        "macro_rules",
+        None,
+        "macro_rules! $1 {\n\t($2) => {\n\t\t$0\n\t};\n}",
+        None,
+    );
+    acc.add_snippet(ctx, cap, "pub(crate)", None, "pub(crate) $0", None);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
+    use crate::completion::{
+        test_utils::{check_edit, do_completion},
+        CompletionItem, CompletionItemKind, CompletionKind, InsertTextFormat,
+    };
     use insta::assert_debug_snapshot;
 
     fn do_snippet_completion(code: &str) -> Vec<CompletionItem> {
@@ -89,6 +114,9 @@ mod tests {
                 delete: 17..17,
                 insert: "eprintln!(\"$0 = {:?}\", $0);",
                 kind: Snippet,
+                documentation: Documentation(
+                    "Prints a value with its `Debug` implementation to stderr.",
+                ),
             },
             CompletionItem {
                 label: "ppd",
@@ -96,12 +124,60 @@ mod tests {
                 delete: 17..17,
                 insert: "eprintln!(\"$0 = {:#?}\", $0);",
                 kind: Snippet,
+                documentation: Documentation(
+                    "Pretty-prints a value with its `Debug` implementation to stderr.",
+                ),
             },
         ]
         "###
                 );
     }
 
+    #[test]
+    fn synthesized_snippets_have_snippet_kind() {
+        let completions = do_snippet_completion(r"fn foo(x: i32) { <|> }");
+        let pd = completions.iter().find(|it| it.label() == "pd").unwrap();
+        assert_eq!(pd.kind(), Some(CompletionItemKind::Snippet));
+        assert_eq!(pd.insert_text_format(), InsertTextFormat::Snippet);
+    }
+
+    #[test]
+    fn completes_closures_for_fn_arguments() {
+        check_edit(
+            "|p0, p1| …",
+            r#"
+fn on_event(f: impl Fn(i32, String)) {}
+fn main() { on_event(<|>) }
+"#,
+            r#"
+fn on_event(f: impl Fn(i32, String)) {}
+fn main() { on_event(|${1:p0}, ${2:p1}| $0) }
+"#,
+        );
+        check_edit(
+            "|p0, p1| …",
+            r#"
+fn on_exit<F>(f: F) where F: FnOnce(i32, &str) {}
+fn main() { on_exit(<|>) }
+"#,
+            r#"
+fn on_exit<F>(f: F) where F: FnOnce(i32, &str) {}
+fn main() { on_exit(|${1:p0}, ${2:p1}| $0) }
+"#,
+        );
+        check_edit(
+            "|code, reason| …",
+            r#"
+fn on_exit(f: fn(code: i32, reason: &str)) {}
+fn main() { on_exit(<|>) }
+"#,
+            r#"
+fn on_exit(f: fn(code: i32, reason: &str)) {}
+fn main() { on_exit(|${1:code}, ${2:reason}| $0) }
+"#,
+        );
+    }
+
     #[test]
     fn should_not_complete_snippets_in_path() {
         assert_debug_snapshot!(
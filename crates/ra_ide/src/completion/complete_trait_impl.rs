@@ -3,7 +3,8 @@
 //! This module adds the completion items related to implementing associated
 //! items within a `impl Trait for Struct` block. The current context node
 //! must be within either a `FN_DEF`, `TYPE_ALIAS_DEF`, or `CONST_DEF` node
-//! and an direct child of an `IMPL_DEF`.
+//! and an direct child of an `IMPL_DEF`. Between the items of the `impl`,
+//! complete stubs with a `todo!()` body are offered instead.
 //!
 //! # Examples
 //!
@@ -34,10 +35,11 @@
 use hir::{self, Docs, HasSource};
 use ra_assists::utils::get_missing_assoc_items;
 use ra_syntax::{
-    ast::{self, edit, ImplDef},
+    ast::{self, edit, edit::IndentLevel, ImplDef},
     AstNode, SyntaxKind, SyntaxNode, TextRange, T,
 };
 use ra_text_edit::TextEdit;
+use test_utils::mark;
 
 use crate::{
     completion::{
@@ -47,19 +49,42 @@ use crate::{
 };
 
 pub(crate) fn complete_trait_impl(acc: &mut Completions, ctx: &CompletionContext) {
+    if let Some((impl_def, item_list)) = empty_slot_impl(ctx) {
+        // Nothing is typed yet, so offer complete stubs for the missing items.
+        let range = ctx.source_range();
+        let item_indent = IndentLevel::from_node(&item_list) + 1;
+        for item in get_missing_assoc_items(&ctx.sema, &impl_def) {
+            match item {
+                hir::AssocItem::Function(fn_item) => {
+                    if has_default_body(ctx, fn_item) && !ctx.config.stub_default_methods {
+                        mark::hit!(skips_stubs_for_default_methods);
+                        continue;
+                    }
+                    add_function_impl(range, acc, ctx, fn_item, Some(item_indent))
+                }
+                hir::AssocItem::TypeAlias(type_item) => {
+                    add_type_alias_impl(range, acc, ctx, type_item)
+                }
+                hir::AssocItem::Const(const_item) => add_const_impl(range, acc, ctx, const_item),
+            }
+        }
+        return;
+    }
+
     if let Some((trigger, impl_def)) = completion_match(ctx) {
+        let range = TextRange::new(trigger.text_range().start(), ctx.source_range().end());
         match trigger.kind() {
             SyntaxKind::NAME_REF => get_missing_assoc_items(&ctx.sema, &impl_def)
                 .into_iter()
                 .for_each(|item| match item {
                     hir::AssocItem::Function(fn_item) => {
-                        add_function_impl(&trigger, acc, ctx, fn_item)
+                        add_function_impl(range, acc, ctx, fn_item, None)
                     }
                     hir::AssocItem::TypeAlias(type_item) => {
-                        add_type_alias_impl(&trigger, acc, ctx, type_item)
+                        add_type_alias_impl(range, acc, ctx, type_item)
                     }
                     hir::AssocItem::Const(const_item) => {
-                        add_const_impl(&trigger, acc, ctx, const_item)
+                        add_const_impl(range, acc, ctx, const_item)
                     }
                 }),
 
@@ -71,7 +96,7 @@ pub(crate) fn complete_trait_impl(acc: &mut Completions, ctx: &CompletionContext
                         _ => None,
                     })
                 {
-                    add_function_impl(&trigger, acc, ctx, missing_fn);
+                    add_function_impl(range, acc, ctx, missing_fn, None);
                 }
             }
 
@@ -83,7 +108,7 @@ pub(crate) fn complete_trait_impl(acc: &mut Completions, ctx: &CompletionContext
                         _ => None,
                     })
                 {
-                    add_type_alias_impl(&trigger, acc, ctx, missing_fn);
+                    add_type_alias_impl(range, acc, ctx, missing_fn);
                 }
             }
 
@@ -95,7 +120,7 @@ pub(crate) fn complete_trait_impl(acc: &mut Completions, ctx: &CompletionContext
                         _ => None,
                     })
                 {
-                    add_const_impl(&trigger, acc, ctx, missing_fn);
+                    add_const_impl(range, acc, ctx, missing_fn);
                 }
             }
 
@@ -104,6 +129,18 @@ pub(crate) fn complete_trait_impl(acc: &mut Completions, ctx: &CompletionContext
     }
 }
 
+/// Finds the trait impl and its item list if the cursor is between its items,
+/// like `impl Trait for S { <|> }`.
+fn empty_slot_impl(ctx: &CompletionContext) -> Option<(ImplDef, SyntaxNode)> {
+    let item_list = ctx.token.parent();
+    if item_list.kind() != SyntaxKind::ITEM_LIST {
+        return None;
+    }
+    let impl_def = item_list.parent().and_then(ast::ImplDef::cast)?;
+    impl_def.target_trait()?;
+    Some((impl_def, item_list))
+}
+
 fn completion_match(ctx: &CompletionContext) -> Option<(SyntaxNode, ImplDef)> {
     let (trigger, impl_def_offset) = ctx.token.ancestors().find_map(|p| match p.kind() {
         SyntaxKind::FN_DEF
@@ -120,10 +157,11 @@ fn completion_match(ctx: &CompletionContext) -> Option<(SyntaxNode, ImplDef)> {
 }
 
 fn add_function_impl(
-    fn_def_node: &SyntaxNode,
+    range: TextRange,
     acc: &mut Completions,
     ctx: &CompletionContext,
     func: hir::Function,
+    stub_indent: Option<IndentLevel>,
 ) {
     let signature = FunctionSignature::from_hir(ctx.db, func);
 
@@ -135,33 +173,47 @@ fn add_function_impl(
         format!("fn {}()", fn_name)
     };
 
-    let builder = CompletionItem::new(CompletionKind::Magic, ctx.source_range(), label)
+    let mut builder = CompletionItem::new(CompletionKind::Magic, ctx.source_range(), label)
         .lookup_by(fn_name)
         .set_documentation(func.docs(ctx.db));
+    if stub_indent.is_some() {
+        let note = if has_default_body(ctx, func) { "overrides default" } else { "required" };
+        builder = builder.detail(note);
+    }
 
     let completion_kind = if func.has_self_param(ctx.db) {
         CompletionItemKind::Method
     } else {
         CompletionItemKind::Function
     };
-    let range = TextRange::new(fn_def_node.text_range().start(), ctx.source_range().end());
-
-    match ctx.config.snippet_cap {
-        Some(cap) => {
+    match (ctx.config.snippet_cap, stub_indent) {
+        (Some(cap), None) => {
             let snippet = format!("{} {{\n    $0\n}}", signature);
             builder.snippet_edit(cap, TextEdit::replace(range, snippet))
         }
-        None => {
+        (Some(cap), Some(indent)) => {
+            let snippet = format!("{} {{\n{}${{0:todo!()}}\n{}}}", signature, indent + 1, indent);
+            builder.snippet_edit(cap, TextEdit::replace(range, snippet))
+        }
+        (None, None) => {
             let header = format!("{} {{", signature);
             builder.text_edit(TextEdit::replace(range, header))
         }
+        (None, Some(indent)) => {
+            let stub = format!("{} {{\n{}todo!()\n{}}}", signature, indent + 1, indent);
+            builder.text_edit(TextEdit::replace(range, stub))
+        }
     }
     .kind(completion_kind)
     .add_to(acc);
 }
 
+fn has_default_body(ctx: &CompletionContext, func: hir::Function) -> bool {
+    func.source(ctx.db).value.body().is_some()
+}
+
 fn add_type_alias_impl(
-    type_def_node: &SyntaxNode,
+    range: TextRange,
     acc: &mut Completions,
     ctx: &CompletionContext,
     type_alias: hir::TypeAlias,
@@ -170,8 +222,6 @@ fn add_type_alias_impl(
 
     let snippet = format!("type {} = ", alias_name);
 
-    let range = TextRange::new(type_def_node.text_range().start(), ctx.source_range().end());
-
     CompletionItem::new(CompletionKind::Magic, ctx.source_range(), snippet.clone())
         .text_edit(TextEdit::replace(range, snippet))
         .lookup_by(alias_name)
@@ -181,7 +231,7 @@ fn add_type_alias_impl(
 }
 
 fn add_const_impl(
-    const_def_node: &SyntaxNode,
+    range: TextRange,
     acc: &mut Completions,
     ctx: &CompletionContext,
     const_: hir::Const,
@@ -191,8 +241,6 @@ fn add_const_impl(
     if let Some(const_name) = const_name {
         let snippet = make_const_compl_syntax(&const_.source(ctx.db).value);
 
-        let range = TextRange::new(const_def_node.text_range().start(), ctx.source_range().end());
-
         CompletionItem::new(CompletionKind::Magic, ctx.source_range(), snippet.clone())
             .text_edit(TextEdit::replace(range, snippet))
             .lookup_by(const_name)
@@ -227,9 +275,14 @@ fn make_const_compl_syntax(const_: &ast::ConstDef) -> String {
 
 #[cfg(test)]
 mod tests {
+    use expect::expect;
     use insta::assert_debug_snapshot;
+    use test_utils::mark;
 
-    use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
+    use crate::completion::{
+        test_utils::{check_edit, completion_list_with_options, do_completion},
+        CompletionConfig, CompletionItem, CompletionKind,
+    };
 
     fn complete(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Magic)
@@ -553,4 +606,109 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn completes_stubs_for_missing_items() {
+        check_edit(
+            "area",
+            r#"
+trait Shape {
+    fn area(&self) -> f64;
+    fn name(&self) -> String;
+}
+struct Circle;
+impl Shape for Circle {
+    fn name(&self) -> String { "circle".to_string() }
+    <|>
+}
+"#,
+            r#"
+trait Shape {
+    fn area(&self) -> f64;
+    fn name(&self) -> String;
+}
+struct Circle;
+impl Shape for Circle {
+    fn name(&self) -> String { "circle".to_string() }
+    fn area(&self) -> f64 {
+        ${0:todo!()}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn indents_stubs_to_the_item_level() {
+        check_edit(
+            "area",
+            r#"
+trait Shape { fn area(&self) -> f64; }
+mod shapes {
+    struct Circle;
+    impl super::Shape for Circle {
+        <|>
+    }
+}
+"#,
+            r#"
+trait Shape { fn area(&self) -> f64; }
+mod shapes {
+    struct Circle;
+    impl super::Shape for Circle {
+        fn area(&self) -> f64 {
+            ${0:todo!()}
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn stubs_for_default_methods_are_configurable() {
+        let fixture = r#"
+trait Shape {
+    fn area(&self) -> f64;
+    fn name(&self) -> String { String::new() }
+}
+struct Circle;
+impl Shape for Circle {
+    <|>
+}
+"#;
+        let actual =
+            completion_list_with_options(fixture, CompletionKind::Magic, &Default::default());
+        expect![[r#"
+            me fn area(..) required
+            me fn name(..) overrides default
+        "#]]
+        .assert_eq(&actual);
+
+        mark::check!(skips_stubs_for_default_methods);
+        let config = CompletionConfig { stub_default_methods: false, ..Default::default() };
+        let actual = completion_list_with_options(fixture, CompletionKind::Magic, &config);
+        expect![[r#"
+            me fn area(..) required
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[test]
+    fn no_stubs_in_inherent_impls() {
+        let completions = complete(
+            r"
+            trait Test {
+                fn test();
+            }
+
+            struct T1;
+
+            impl T1 {
+                <|>
+            }
+            ",
+        );
+        assert_debug_snapshot!(completions, @r###"[]"###);
+    }
 }
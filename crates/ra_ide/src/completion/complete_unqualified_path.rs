@@ -3,9 +3,16 @@
 use hir::ScopeDef;
 use test_utils::mark;
 
-use crate::completion::{CompletionContext, Completions};
-use hir::{Adt, ModuleDef, Type};
-use ra_syntax::AstNode;
+use crate::completion::{
+    fuzzy::matches_word_starts, CompletionContext, CompletionItem, CompletionItemKind,
+    CompletionKind, Completions,
+};
+use hir::{
+    Adt, AssocItem, BuiltinType, Docs, HasSource, HasVisibility, HirDisplay, ModuleDef,
+    ModuleSource, PathResolution, Type, Visibility,
+};
+use ra_syntax::{ast, AstNode};
+use rustc_hash::FxHashSet;
 
 pub(super) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionContext) {
     if !(ctx.is_trivial_path || ctx.is_pat_binding_or_const) {
@@ -22,6 +29,18 @@ pub(super) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionC
         complete_enum_variants(acc, ctx, ty);
     }
 
+    if let Some(path) = &ctx.type_arg_list_path {
+        complete_assoc_type_bindings(acc, ctx, path);
+    }
+
+    if ctx.const_param.is_some() || ctx.is_array_len {
+        complete_const_arg_literals(acc, ctx);
+    }
+
+    if ctx.is_type_bound {
+        complete_marker_traits(acc, ctx);
+    }
+
     if ctx.is_pat_binding_or_const {
         return;
     }
@@ -35,8 +54,232 @@ pub(super) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionC
                 }
             }
         }
+        if ctx.is_type_bound && !is_trait_or_module(&res) {
+            return;
+        }
+        if ctx.is_dyn_trait_extra_bound && !is_auto_trait_or_module(ctx, &res) {
+            mark::hit!(completes_only_auto_traits_after_dyn_trait);
+            return;
+        }
+        if ctx.self_bound_trait.as_deref() == Some(name.to_string().as_str()) {
+            // A trait can't be its own supertrait.
+            mark::hit!(skips_trait_in_its_self_bounds);
+            return;
+        }
+        if ctx.enum_discriminant.is_some() && !is_const_evaluable(ctx, &res) {
+            mark::hit!(completes_only_consts_in_discriminants);
+            return;
+        }
+        if ctx.const_param.is_some() && !is_const_evaluable(ctx, &res) {
+            mark::hit!(completes_only_consts_in_const_args);
+            return;
+        }
+        if ctx.is_array_len && !is_const_evaluable(ctx, &res) {
+            mark::hit!(completes_only_consts_in_array_lengths);
+            return;
+        }
+        if ctx.is_cast_target && !is_cast_target_type(&res) {
+            mark::hit!(completes_only_castable_types);
+            return;
+        }
         acc.add_resolution(ctx, name.to_string(), &res)
     });
+
+    if ctx.use_item_syntax.is_none()
+        && !ctx.is_type_bound
+        && !ctx.is_cast_target
+        && ctx.enum_discriminant.is_none()
+        && ctx.const_param.is_none()
+        && !ctx.is_array_len
+    {
+        complete_inline_module_items(acc, ctx);
+    }
+
+    complete_ufcs_methods(acc, ctx);
+}
+
+/// Items which may be used in a const context, like an enum discriminant.
+/// Only `const fn`s can be called there.
+fn is_const_evaluable(ctx: &CompletionContext, res: &ScopeDef) -> bool {
+    match res {
+        ScopeDef::ModuleDef(ModuleDef::Function(it)) => {
+            it.source(ctx.db).value.const_token().is_some()
+        }
+        ScopeDef::ModuleDef(ModuleDef::Const(_)) | ScopeDef::ModuleDef(ModuleDef::Module(_)) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Bounds are traits, possibly behind a path like `fmt::Display`.
+fn is_trait_or_module(res: &ScopeDef) -> bool {
+    matches!(
+        res,
+        ScopeDef::ModuleDef(ModuleDef::Trait(_)) | ScopeDef::ModuleDef(ModuleDef::Module(_))
+    )
+}
+
+/// Only auto traits can be added to a trait object, like `dyn Trait + Send`,
+/// possibly behind a path like `marker::Send`.
+fn is_auto_trait_or_module(ctx: &CompletionContext, res: &ScopeDef) -> bool {
+    match res {
+        ScopeDef::ModuleDef(ModuleDef::Trait(it)) => it.is_auto(ctx.db),
+        ScopeDef::ModuleDef(ModuleDef::Module(_)) => true,
+        _ => false,
+    }
+}
+
+/// Only primitive types can be the target of an `as` cast, possibly through a
+/// type alias like `c_int` or behind a path like `libc::c_int`.
+fn is_cast_target_type(res: &ScopeDef) -> bool {
+    match res {
+        ScopeDef::ModuleDef(ModuleDef::BuiltinType(it)) => *it != BuiltinType::Str,
+        ScopeDef::ModuleDef(ModuleDef::TypeAlias(_))
+        | ScopeDef::ModuleDef(ModuleDef::Module(_)) => true,
+        _ => false,
+    }
+}
+
+/// Completes `?Sized` and the marker traits of `core` in bounds, like
+/// `T: <|>`, which are not in scope, like without a prelude. Their path is
+/// inserted, like `core::marker::Send`.
+fn complete_marker_traits(acc: &mut Completions, ctx: &CompletionContext) {
+    if ctx.is_type_param_bound {
+        CompletionItem::new(CompletionKind::Magic, ctx.source_range(), "?Sized")
+            .kind(CompletionItemKind::Trait)
+            .detail("maybe sized")
+            .add_to(acc);
+    }
+
+    let (famous_defs, module) = match (ctx.famous_defs(), ctx.scope().module()) {
+        (Some(famous_defs), Some(module)) => (famous_defs, module),
+        _ => return,
+    };
+    let mut in_scope = FxHashSet::default();
+    ctx.scope().process_all_names(&mut |_, def| {
+        if let ScopeDef::ModuleDef(ModuleDef::Trait(it)) = def {
+            in_scope.insert(it);
+        }
+    });
+    let marker_traits = [
+        famous_defs.core_marker_Send(),
+        famous_defs.core_marker_Sync(),
+        famous_defs.core_marker_Unpin(),
+        famous_defs.core_marker_Sized(),
+        famous_defs.core_marker_Copy(),
+    ];
+    for trait_ in marker_traits.iter().flatten() {
+        let is_auto = trait_.is_auto(ctx.db);
+        if in_scope.contains(trait_) || (ctx.is_dyn_trait_extra_bound && !is_auto) {
+            continue;
+        }
+        let path = match module.find_use_path(ctx.db, ModuleDef::Trait(*trait_)) {
+            Some(it) => it,
+            None => continue,
+        };
+        CompletionItem::new(
+            CompletionKind::Magic,
+            ctx.source_range(),
+            trait_.name(ctx.db).to_string(),
+        )
+        .kind(CompletionItemKind::Trait)
+        .detail(if is_auto { "auto trait" } else { "marker trait" })
+        .insert_text(path.to_string())
+        .add_to(acc);
+    }
+}
+
+/// Completes items of private inline child modules, which are reachable, but
+/// not in scope by name, like `Circle` in
+/// `mod shapes { pub(super) struct Circle; }`. Their qualified path is
+/// inserted.
+///
+/// Only items with a restricted visibility are completed: they can't be
+/// re-exported, so the child module is the only path to them. Public items
+/// are left to import completion.
+fn complete_inline_module_items(acc: &mut Completions, ctx: &CompletionContext) {
+    let module = match ctx.scope().module() {
+        Some(it) => it,
+        None => return,
+    };
+    let mut in_scope = FxHashSet::default();
+    ctx.scope().process_all_names(&mut |name, _| {
+        in_scope.insert(name);
+    });
+
+    for child in module.children(ctx.db) {
+        if !matches!(child.definition_source(ctx.db).value, ModuleSource::Module(_))
+            || is_public(module.visibility_of(ctx.db, &ModuleDef::Module(child)))
+        {
+            continue;
+        }
+        let child_name = match child.name(ctx.db) {
+            Some(it) => it,
+            None => continue,
+        };
+        for (name, def) in child.scope(ctx.db, Some(module)) {
+            let def_visibility = match def {
+                ScopeDef::ModuleDef(it) => child.visibility_of(ctx.db, &it),
+                _ => continue,
+            };
+            if in_scope.contains(&name) || is_public(def_visibility) {
+                continue;
+            }
+            mark::hit!(completes_inline_module_items_qualified);
+            acc.add_qualified_resolution(ctx, child_name.to_string(), name.to_string(), &def);
+        }
+    }
+}
+
+fn is_public(visibility: Option<Visibility>) -> bool {
+    matches!(visibility, Some(Visibility::Public))
+}
+
+/// Completes `Vec::push` for `pu<|>` in expression position: the methods of
+/// the types in scope whose name matches the typed text, called as associated
+/// functions.
+fn complete_ufcs_methods(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let name_ref = ctx.name_ref_syntax.as_ref()?;
+    let path = name_ref.syntax().ancestors().find_map(ast::Path::cast)?;
+    path.syntax().parent().and_then(ast::PathExpr::cast)?;
+    let typed = ctx.typed_text();
+    if typed.is_empty() {
+        // Every method of every type in scope would be offered otherwise.
+        return None;
+    }
+    let krate = ctx.krate?;
+    let module = ctx.scope().module()?;
+
+    let mut adts = Vec::new();
+    ctx.scope().process_all_names(&mut |_, def| {
+        if let ScopeDef::ModuleDef(ModuleDef::Adt(adt)) = def {
+            adts.push(adt);
+        }
+    });
+    for adt in adts {
+        let adt_path = match module.find_use_path(ctx.db, ModuleDef::Adt(adt)) {
+            Some(it) => it.to_string(),
+            None => continue,
+        };
+        adt.ty(ctx.db).iterate_assoc_items(ctx.db, krate, |item| {
+            let func = match item {
+                AssocItem::Function(it) => it,
+                _ => return None,
+            };
+            let name = func.name(ctx.db).to_string();
+            if !func.has_self_param(ctx.db)
+                || !item.is_visible_from(ctx.db, module)
+                || !matches_word_starts(typed, &name)
+            {
+                return None;
+            }
+            mark::hit!(completes_methods_as_associated_functions);
+            acc.add_ufcs_method(ctx, func, format!("{}::{}", adt_path, name));
+            None::<()>
+        });
+    }
+    Some(())
 }
 
 fn complete_enum_variants(acc: &mut Completions, ctx: &CompletionContext, ty: &Type) {
@@ -60,20 +303,610 @@ fn complete_enum_variants(acc: &mut Completions, ctx: &CompletionContext, ty: &T
                 }
             }
         }
+
+        if implements_default(ctx, ty) {
+            if let Some(path) = module.find_use_path(ctx.db, ModuleDef::Adt(Adt::Enum(enum_data))) {
+                let call = format!("{}::default()", path);
+                CompletionItem::new(CompletionKind::Magic, ctx.source_range(), call.clone())
+                    .kind(CompletionItemKind::Function)
+                    .detail("fn default() -> Self")
+                    .insert_text(call)
+                    .add_to(acc);
+            }
+        }
+    }
+}
+
+/// Whether `ty` implements `core::default::Default`.
+fn implements_default(ctx: &CompletionContext, ty: &Type) -> bool {
+    ctx.famous_defs()
+        .and_then(|it| it.core_default_Default())
+        .map_or(false, |it| ty.impls_trait(ctx.db, it, &[]))
+}
+
+/// Completes `0` in `Arr::<<|>>` if the const parameter has an integer type.
+fn complete_const_arg_literals(acc: &mut Completions, ctx: &CompletionContext) {
+    let ty = match ctx.expected_const_type() {
+        Some(it) => it,
+        None => return,
+    };
+    if !matches!(ty.as_builtin(), Some(BuiltinType::Int(_))) {
+        return;
+    }
+    CompletionItem::new(CompletionKind::Magic, ctx.source_range(), "0")
+        .kind(CompletionItemKind::Const)
+        .detail(ty.display(ctx.db).to_string())
+        .add_to(acc);
+}
+
+/// Completes `Item = ` in `Iterator<<|>>`.
+fn complete_assoc_type_bindings(acc: &mut Completions, ctx: &CompletionContext, path: &ast::Path) {
+    let trait_ = match ctx.sema.resolve_path(path) {
+        Some(PathResolution::Def(ModuleDef::Trait(it))) => it,
+        _ => return,
+    };
+    for item in trait_.items(ctx.db) {
+        if let AssocItem::TypeAlias(alias) = item {
+            let name = alias.name(ctx.db).to_string();
+            CompletionItem::new(
+                CompletionKind::Reference,
+                ctx.source_range(),
+                format!("{} =", name),
+            )
+            .kind(CompletionItemKind::TypeAlias)
+            .insert_text(format!("{} = ", name))
+            .lookup_by(name)
+            .set_documentation(alias.docs(ctx.db))
+            .add_to(acc);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use expect::{expect, Expect};
     use insta::assert_debug_snapshot;
     use test_utils::mark;
 
-    use crate::completion::{test_utils::do_completion, CompletionItem, CompletionKind};
+    use crate::completion::{
+        test_utils::{check_edit, completion_list, do_completion},
+        CompletionItem, CompletionKind,
+    };
 
     fn do_reference_completion(ra_fixture: &str) -> Vec<CompletionItem> {
         do_completion(ra_fixture, CompletionKind::Reference)
     }
 
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_assoc_type_bindings() {
+        check(
+            r#"
+trait Iterator { type Item; }
+struct S;
+fn foo() -> impl Iterator<<|>> { S }
+"#,
+            expect![[r#"
+                ta Item =
+                tt Iterator
+                st S
+                fn foo() fn foo() -> impl Iterator<>
+            "#]],
+        );
+        check(
+            r#"
+trait Iterator { type Item; }
+struct S;
+fn foo() -> impl Iterator<Item = <|>> { S }
+"#,
+            expect![[r#"
+                tt Iterator
+                st S
+                fn foo() fn foo() -> impl Iterator<Item = >
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_methods_as_associated_functions() {
+        mark::check!(completes_methods_as_associated_functions);
+        check_edit(
+            "Vec::push",
+            r#"
+struct Vec;
+impl Vec {
+    fn new() -> Vec { Vec }
+    fn push(&mut self, value: u32) {}
+    fn pop(&mut self) -> u32 { 0 }
+}
+fn main() { let v: Vec = pu<|> }
+"#,
+            r#"
+struct Vec;
+impl Vec {
+    fn new() -> Vec { Vec }
+    fn push(&mut self, value: u32) {}
+    fn pop(&mut self) -> u32 { 0 }
+}
+fn main() { let v: Vec = Vec::push(${1:self}, ${2:value})$0 }
+"#,
+        );
+        check(
+            r#"
+struct Vec;
+impl Vec {
+    fn new() -> Vec { Vec }
+    fn push(&mut self, value: u32) {}
+    fn pop(&mut self) -> u32 { 0 }
+}
+struct Stack;
+impl Stack {
+    fn peek(&self) -> u32 { 0 }
+    fn len(&self) -> usize { 0 }
+}
+fn main() { p<|> }
+"#,
+            expect![[r#"
+                st Stack
+                me Stack::peek(…) fn peek(&self) -> u32
+                st Vec
+                me Vec::pop(…) fn pop(&mut self) -> u32
+                me Vec::push(…) fn push(&mut self, value: u32)
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn boosts_ufcs_methods_returning_expected_type() {
+        mark::check!(boosts_ufcs_methods_returning_expected_type);
+        let boosted: Vec<String> = do_reference_completion(
+            r#"
+struct Vec;
+impl Vec {
+    fn push(&mut self, value: u32) {}
+    fn pop(&mut self) -> u32 { 0 }
+}
+fn main() { let n: u32 = p<|> }
+"#,
+        )
+        .into_iter()
+        .filter(|it| it.score().is_some())
+        .map(|it| it.label().to_string())
+        .collect();
+        assert_eq!(boosted, vec!["Vec::pop(…)"]);
+    }
+
+    #[test]
+    fn completes_marker_traits_in_bounds() {
+        let check_magic = |ra_fixture: &str, expect: Expect| {
+            expect.assert_eq(&completion_list(ra_fixture, CompletionKind::Magic))
+        };
+        check_magic(
+            r#"
+//- /main.rs
+fn f<T: <|>>() {}
+//- /core/lib.rs
+pub mod marker {
+    pub unsafe auto trait Send {}
+    pub unsafe auto trait Sync {}
+    pub auto trait Unpin {}
+    pub trait Sized {}
+    pub trait Copy {}
+}
+"#,
+            expect![[r#"
+                tt ?Sized maybe sized
+                tt Copy marker trait
+                tt Send auto trait
+                tt Sized marker trait
+                tt Sync auto trait
+                tt Unpin auto trait
+            "#]],
+        );
+        check_magic(
+            r#"
+//- /main.rs
+use core::marker::Send;
+fn f<T>() where T: Clone + <|> {}
+//- /core/lib.rs
+pub mod marker {
+    pub unsafe auto trait Send {}
+    pub unsafe auto trait Sync {}
+    pub auto trait Unpin {}
+    pub trait Sized {}
+    pub trait Copy {}
+}
+"#,
+            expect![[r#"
+                tt ?Sized maybe sized
+                tt Copy marker trait
+                tt Sized marker trait
+                tt Sync auto trait
+                tt Unpin auto trait
+            "#]],
+        );
+        check_magic(
+            r#"
+//- /main.rs
+trait Shape: <|> {}
+//- /core/lib.rs
+pub mod marker {
+    pub unsafe auto trait Send {}
+    pub unsafe auto trait Sync {}
+    pub auto trait Unpin {}
+    pub trait Sized {}
+    pub trait Copy {}
+}
+"#,
+            expect![[r#"
+                tt Copy marker trait
+                tt Send auto trait
+                tt Sized marker trait
+                tt Sync auto trait
+                tt Unpin auto trait
+            "#]],
+        );
+        check_magic(
+            r#"
+fn f<T: <|>>() {}
+"#,
+            expect![[r#"
+                tt ?Sized maybe sized
+            "#]],
+        );
+        check_edit(
+            "Send",
+            r#"
+//- /main.rs
+fn f<T: <|>>() {}
+//- /core/lib.rs
+pub mod marker { pub unsafe auto trait Send {} }
+"#,
+            r#"
+fn f<T: core::marker::Send>() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_default_of_expected_enums() {
+        let check_magic = |ra_fixture: &str, expect: Expect| {
+            expect.assert_eq(&completion_list(ra_fixture, CompletionKind::Magic))
+        };
+        check_magic(
+            r#"
+//- /main.rs
+#[derive(Default)]
+enum Shape { Circle, Square }
+fn main() { let shape: Shape = S<|> }
+//- /core/lib.rs
+pub mod default { pub trait Default { fn default() -> Self; } }
+"#,
+            expect![[r#"
+                fn Shape::default() fn default() -> Self
+            "#]],
+        );
+        check_magic(
+            r#"
+//- /main.rs
+enum Shape { Circle, Square }
+fn main() { let shape: Shape = S<|> }
+//- /core/lib.rs
+pub mod default { pub trait Default { fn default() -> Self; } }
+"#,
+            expect![[""]],
+        );
+        check_magic(
+            r#"
+trait Default { fn default() -> Self; }
+enum Shape { Circle, Square }
+impl Default for Shape { fn default() -> Self { Shape::Circle } }
+fn main() { let shape: Shape = S<|> }
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn completes_auto_traits_and_lifetimes_after_dyn_trait() {
+        mark::check!(completes_only_auto_traits_after_dyn_trait);
+        check(
+            r#"
+#![feature(auto_traits)]
+auto trait Leak {}
+trait Draw {}
+mod marker {}
+struct Canvas<'a>(Box<dyn Draw + <|>>);
+"#,
+            expect![[r#"
+                tp 'a
+                tp 'static
+                tt Leak
+                md marker
+            "#]],
+        );
+        expect![[r#"
+            tt Send auto trait
+            tt Sync auto trait
+            tt Unpin auto trait
+        "#]]
+        .assert_eq(&completion_list(
+            r#"
+//- /main.rs
+trait Draw {}
+fn f(_: &dyn Draw + <|>) {}
+//- /core/lib.rs
+pub mod marker {
+    pub unsafe auto trait Send {}
+    pub unsafe auto trait Sync {}
+    pub auto trait Unpin {}
+    pub trait Sized {}
+    pub trait Copy {}
+}
+"#,
+            CompletionKind::Magic,
+        ));
+    }
+
+    #[test]
+    fn completes_supertraits_in_self_bounds_of_traits() {
+        mark::check!(skips_trait_in_its_self_bounds);
+        check(
+            r#"
+trait Display {}
+struct S;
+trait Shape where Self: <|> {}
+"#,
+            expect![[r#"
+                tt Display
+            "#]],
+        );
+        let actual = completion_list(
+            r#"
+//- /main.rs
+trait Shape where Self: <|> {}
+//- /core/lib.rs
+pub mod marker {
+    pub unsafe auto trait Send {}
+    pub unsafe auto trait Sync {}
+    pub auto trait Unpin {}
+    pub trait Sized {}
+    pub trait Copy {}
+}
+"#,
+            CompletionKind::Magic,
+        );
+        expect![[r#"
+            tt Copy marker trait
+            tt Send auto trait
+            tt Sized marker trait
+            tt Sync auto trait
+            tt Unpin auto trait
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_traits_and_modules_in_where_clause_bounds() {
+        check(
+            r#"
+trait Display {}
+struct S;
+mod m {}
+fn f<T>() where T: <|> {}
+"#,
+            expect![[r#"
+                tt Display
+                md m
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_traits_after_plus_in_bounds() {
+        check(
+            r#"
+trait Display {}
+unsafe auto trait Send {}
+struct S;
+fn f<T: Display + <|>>() {}
+"#,
+            expect![[r#"
+                tt Display
+                tt Send
+            "#]],
+        );
+        check(
+            r#"
+trait Display {}
+trait Debug {}
+struct S;
+fn f<T>() where T: Display + <|> {}
+"#,
+            expect![[r#"
+                tt Debug
+                tt Display
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_only_consts_in_discriminants() {
+        mark::check!(completes_only_consts_in_discriminants);
+        check(
+            r#"
+const BASE: u32 = 8;
+const fn shifted(n: u32) -> u32 { n << 4 }
+struct S;
+mod flags {}
+enum E { A = BASE, B = <|> }
+"#,
+            expect![[r#"
+                ct BASE
+                md flags
+                fn shifted(…) const fn shifted(n: u32) -> u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_consts_in_const_args() {
+        mark::check!(completes_only_consts_in_const_args);
+        mark::check!(boosts_consts_of_const_param_type);
+        let fixture = r#"
+struct Arr<T, const N: usize>([T; N]);
+const LEN: usize = 4;
+const NAME: &str = "arr";
+struct S;
+fn f(a: Arr<u8, <|>>) {}
+"#;
+        check(
+            fixture,
+            expect![[r#"
+                ct LEN
+                ct NAME
+            "#]],
+        );
+        let boosted: Vec<String> = do_reference_completion(fixture)
+            .into_iter()
+            .filter(|it| it.score().is_some())
+            .map(|it| it.label().to_string())
+            .collect();
+        assert_eq!(boosted, vec!["LEN"]);
+        expect![[r#"
+            ct 0 usize
+        "#]]
+        .assert_eq(&completion_list(fixture, CompletionKind::Magic));
+    }
+
+    #[test]
+    fn boosts_consts_of_aliased_const_param_type() {
+        let fixture = r#"
+mod arr {
+    type Len = usize;
+    pub struct Arr<const N: Len>;
+}
+type Count = usize;
+const LEN: Count = 4;
+const NAME: &str = "arr";
+fn f(a: arr::Arr<<|>>) {}
+"#;
+        let boosted: Vec<String> = do_reference_completion(fixture)
+            .into_iter()
+            .filter(|it| it.score().is_some())
+            .map(|it| it.label().to_string())
+            .collect();
+        assert_eq!(boosted, vec!["LEN"]);
+        expect![[r#"
+            ct 0 usize
+        "#]]
+        .assert_eq(&completion_list(fixture, CompletionKind::Magic));
+    }
+
+    #[test]
+    fn completes_consts_in_array_lengths() {
+        mark::check!(completes_only_consts_in_array_lengths);
+        let fixture = r#"
+const LEN: usize = 4;
+const NAME: &str = "arr";
+const fn double(n: usize) -> usize { n * 2 }
+struct S;
+fn f(a: [u8; <|>]) {}
+"#;
+        check(
+            fixture,
+            expect![[r#"
+                ct LEN
+                ct NAME
+                fn double(…) const fn double(n: usize) -> usize
+            "#]],
+        );
+        let boosted: Vec<String> = do_reference_completion(fixture)
+            .into_iter()
+            .filter(|it| it.score().is_some())
+            .map(|it| it.label().to_string())
+            .collect();
+        assert_eq!(boosted, vec!["LEN"]);
+        expect![[r#"
+            ct 0 usize
+        "#]]
+        .assert_eq(&completion_list(fixture, CompletionKind::Magic));
+    }
+
+    #[test]
+    fn completes_only_castable_types() {
+        mark::check!(completes_only_castable_types);
+        check(
+            r#"
+mod libc { pub type c_int = i32; }
+use libc::c_int;
+struct S;
+fn f(n: u32) { n as <|> }
+"#,
+            expect![[r#"
+                ta c_int
+                md libc
+            "#]],
+        );
+        check(
+            r#"
+mod libc { pub type c_int = i32; }
+fn f(n: u32) { n as libc::<|> }
+"#,
+            expect![[r#"
+                ta c_int
+            "#]],
+        );
+        let builtins = completion_list(
+            r#"
+fn f(n: u32) { n as <|> }
+"#,
+            CompletionKind::BuiltinType,
+        );
+        assert!(builtins.contains("bt u8\n") && builtins.contains("bt f64\n"));
+        assert!(!builtins.contains("bt str\n"));
+    }
+
+    #[test]
+    fn completes_inline_module_items_qualified() {
+        mark::check!(completes_inline_module_items_qualified);
+        check(
+            r#"
+mod shapes {
+    pub(super) struct Circle;
+    pub struct Square;
+    struct Private;
+}
+pub mod colors {
+    pub(crate) struct Red;
+}
+fn main() { Cir<|> }
+"#,
+            expect![[r#"
+                md colors
+                fn main() fn main()
+                md shapes
+                st shapes::Circle (shapes::Circle)
+            "#]],
+        );
+        check_edit(
+            "Circle",
+            r#"
+mod shapes { pub(super) struct Circle; }
+fn main() { Cir<|> }
+"#,
+            r#"
+mod shapes { pub(super) struct Circle; }
+fn main() { shapes::Circle }
+"#,
+        );
+    }
+
     #[test]
     fn self_fulfilling_completion() {
         mark::check!(self_fulfilling_completion);
@@ -353,7 +1186,7 @@ mod tests {
                 kind: TypeParam,
             },
             CompletionItem {
-                label: "quux()",
+                label: "quux<…>()",
                 source_range: 19..19,
                 delete: 19..19,
                 insert: "quux()$0",
@@ -396,7 +1229,7 @@ mod tests {
                 label: "X<…>",
                 source_range: 21..21,
                 delete: 21..21,
-                insert: "X<$0>",
+                insert: "X<${1:T}>$0",
                 kind: Struct,
                 lookup: "X",
             },
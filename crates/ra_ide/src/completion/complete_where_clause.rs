@@ -0,0 +1,138 @@
+//! Completes the bounds a trait requires of its type arguments in the where
+//! clause of an impl of the trait.
+
+use hir::{HasSource, ModuleDef, PathResolution};
+use ra_syntax::{
+    ast::{self, NameOwner, TypeBoundsOwner, TypeParamsOwner},
+    AstNode,
+};
+use stdx::SepBy;
+
+use crate::completion::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+};
+
+/// Offers `T: Clone` in `impl<T> Convert<T> for S where <|>` for
+/// `trait Convert<U: Clone>`.
+pub(super) fn complete_where_clause(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let impl_def = ctx.token.parent().ancestors().find_map(ast::ImplDef::cast)?;
+    let where_clause = impl_def.where_clause()?;
+    let body_start = impl_def
+        .item_list()
+        .map_or(impl_def.syntax().text_range().end(), |it| it.syntax().text_range().start());
+    if ctx.offset < where_clause.where_token()?.text_range().end() || ctx.offset > body_start {
+        return None;
+    }
+
+    let trait_path = match impl_def.target_trait()? {
+        ast::TypeRef::PathType(it) => it.path()?,
+        _ => return None,
+    };
+    let trait_ = match ctx.sema.resolve_path(&trait_path)? {
+        PathResolution::Def(ModuleDef::Trait(it)) => it,
+        _ => return None,
+    };
+    let trait_def = trait_.source(ctx.db).value;
+    let trait_name = trait_def.name()?;
+    let args: Vec<ast::TypeRef> = trait_path
+        .segment()?
+        .type_arg_list()
+        .into_iter()
+        .flat_map(|it| it.type_args())
+        .filter_map(|it| it.type_ref())
+        .collect();
+    let existing: Vec<String> =
+        where_clause.predicates().map(|it| it.syntax().text().to_string()).collect();
+
+    for (param, arg) in trait_def.type_param_list()?.type_params().zip(args) {
+        let name = match param.name() {
+            Some(it) => it.text().to_string(),
+            None => continue,
+        };
+        let where_bounds = trait_def
+            .where_clause()
+            .into_iter()
+            .flat_map(|it| it.predicates())
+            .filter(|it| it.type_ref().map_or(false, |it| it.syntax().text() == name.as_str()))
+            .filter_map(|it| it.type_bound_list());
+        let bounds: Vec<String> = param
+            .type_bound_list()
+            .into_iter()
+            .chain(where_bounds)
+            .flat_map(|it| it.bounds())
+            .map(|it| it.syntax().text().to_string())
+            .collect();
+        if bounds.is_empty() {
+            continue;
+        }
+        let pred = format!("{}: {}", arg.syntax(), bounds.iter().sep_by(" + "));
+        if existing.contains(&pred) {
+            continue;
+        }
+        CompletionItem::new(CompletionKind::Magic, ctx.source_range(), pred)
+            .kind(CompletionItemKind::Snippet)
+            .detail(format!("required by {}", trait_name.text()))
+            .add_to(acc);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect::{expect, Expect};
+
+    use crate::completion::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_bounds_required_by_trait() {
+        check(
+            r#"
+trait Clone {}
+trait Debug {}
+trait Convert<U: Clone, V, W> where W: Debug + Clone {}
+struct S<T>(T);
+impl<A, B, C> Convert<A, B, Vec<C>> for S<A> where <|> {}
+"#,
+            expect![[r#"
+                sn A: Clone required by Convert
+                sn Vec<C>: Debug + Clone required by Convert
+            "#]],
+        );
+        check_edit(
+            "A: Clone",
+            r#"
+trait Clone {}
+trait Convert<U: Clone> {}
+struct S;
+impl<A> Convert<A> for S where A<|> {}
+"#,
+            r#"
+trait Clone {}
+trait Convert<U: Clone> {}
+struct S;
+impl<A> Convert<A> for S where A: Clone {}
+"#,
+        );
+    }
+
+    #[test]
+    fn skips_bounds_already_in_where_clause() {
+        check(
+            r#"
+trait Clone {}
+trait Convert<U: Clone> {}
+struct S;
+impl<A> Convert<A> for S where A: Clone, <|> {}
+"#,
+            expect![[""]],
+        );
+    }
+}
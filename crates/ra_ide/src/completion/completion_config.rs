@@ -4,20 +4,167 @@
 //! module, and we use to statically check that we only produce snippet
 //! completions if we are allowed to.
 
+use std::{fmt, panic::RefUnwindSafe, sync::Arc};
+
+use crate::completion::{CompletionItem, CompletionItemKind, CompletionScore};
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CompletionConfig {
     pub enable_postfix_completions: bool,
     pub add_call_parenthesis: bool,
     pub add_call_argument_snippets: bool,
+    /// Overrides the two flags above for some kinds of callable items.
+    pub call_parens_per_kind: CallParensPerKind,
+    /// Add a turbofish, like `parse::<$1>()`, when calling a generic function
+    /// whose type parameters can't be inferred from its arguments.
+    pub add_call_turbofish: bool,
+    /// Insert guessed braces, like `vec![]`, when completing a macro call.
+    pub add_macro_braces: bool,
+    /// Offer stubs for trait methods with a default body in an empty slot of
+    /// a trait impl, like `impl Trait for S { <|> }`, to override them.
+    pub stub_default_methods: bool,
     pub snippet_cap: Option<SnippetCap>,
+    /// Without `snippet_cap`, still insert the text of snippets, reporting
+    /// where `$0` would have put the cursor as `CompletionItem::cursor_offset`.
+    pub report_cursor_offsets: bool,
+    /// Complete items which are not in scope yet, adding a `use` for them.
+    pub enable_autoimport_completions: bool,
+    /// Don't complete items marked with `#[unstable]`.
+    pub hide_unstable: bool,
+    /// Don't complete `#[doc(hidden)]` items from other crates.
+    pub respect_doc_hidden: bool,
+    /// Sort items from the prelude, like `Some` or `drop`, after other items.
+    pub deprioritize_prelude: bool,
+    /// Sort items which are referenced elsewhere in the current file before
+    /// other items.
+    pub boost_referenced_items: bool,
+    /// Among items which are otherwise equally relevant, sort shorter labels
+    /// first, like a local `x` before `xml_reader`.
+    pub prefer_shorter_labels: bool,
+    /// Among members which are otherwise equally relevant, sort fields before
+    /// methods or the other way around.
+    pub member_preference: MemberPreference,
+    /// Attach a relevance breakdown to completion items, for debugging.
+    pub explain_relevance: bool,
+    /// Show the number of variants of an enum in its completion detail.
+    pub show_enum_variant_count: bool,
+    /// Show the size and alignment of structs and enums, like
+    /// `(size 16, align 8)`.
+    pub show_type_layout: bool,
+    /// Show the visibility of an item, like `(pub(crate))`.
+    pub show_visibility: bool,
+    /// Mark async functions and methods in their label, like `fetch() async`,
+    /// so they stand out without reading the detail.
+    pub show_async_in_labels: bool,
+    /// Show the module an item is defined in, like `(from std::fmt)`.
+    pub show_item_origin: bool,
+    /// Note how a method takes `self`, like `(by mut ref)`.
+    pub show_self_kind: bool,
+    /// Note which `const fn` calls can be evaluated in a const context.
+    pub show_const_eval: bool,
+    /// List a few methods of a method's return type in its documentation.
+    /// This runs method resolution for every completed method.
+    pub show_method_chains: bool,
+    /// When there is a type to score items against, drop the items scored
+    /// lower than this, including unscored ones.
+    pub min_score: Option<CompletionScore>,
+    /// Attach the full documentation to completion items. By default, only
+    /// its first paragraph is attached, to keep long completion lists small.
+    pub full_documentation: bool,
+    /// The format of the documentation of completion items.
+    pub documentation_format: DocumentationFormat,
+    /// Post-processes every completion item before it is reported.
+    pub item_hook: Option<CompletionItemHook>,
 }
 
 impl CompletionConfig {
     pub fn allow_snippets(&mut self, yes: bool) {
         self.snippet_cap = if yes { Some(SnippetCap { _private: () }) } else { None }
     }
+
+    /// How calls to an item of the given kind should be completed.
+    pub(crate) fn call_parens(&self, kind: CompletionItemKind) -> CallParens {
+        let per_kind = &self.call_parens_per_kind;
+        let overridden = match kind {
+            CompletionItemKind::Function => per_kind.functions,
+            CompletionItemKind::Method => per_kind.methods,
+            CompletionItemKind::EnumVariant => per_kind.enum_variants,
+            _ => None,
+        };
+        overridden.unwrap_or(CallParens {
+            add_parens: self.add_call_parenthesis,
+            add_argument_snippets: self.add_call_argument_snippets,
+        })
+    }
+}
+
+/// Whether to add parentheses, and argument snippets within them, when
+/// completing a call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallParens {
+    pub add_parens: bool,
+    pub add_argument_snippets: bool,
+}
+
+/// Per-kind call completion settings. `None` falls back to
+/// `CompletionConfig::add_call_parenthesis` and
+/// `CompletionConfig::add_call_argument_snippets`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallParensPerKind {
+    pub functions: Option<CallParens>,
+    pub methods: Option<CallParens>,
+    /// Tuple enum variants, like `Some(…)`.
+    pub enum_variants: Option<CallParens>,
+}
+
+/// Which kind of members of a type to sort first, see
+/// `CompletionConfig::member_preference`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemberPreference {
+    None,
+    Fields,
+    Methods,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentationFormat {
+    Markdown,
+    /// Markdown with the formatting stripped, for clients which can't render
+    /// it.
+    PlainText,
+}
+
+/// A function applied to each finished completion item, which allows clients
+/// to adjust its label, insert text or detail.
+#[derive(Clone)]
+pub struct CompletionItemHook(Arc<dyn Fn(&mut CompletionItem) + Send + Sync + RefUnwindSafe>);
+
+impl CompletionItemHook {
+    pub fn new(
+        hook: impl Fn(&mut CompletionItem) + Send + Sync + RefUnwindSafe + 'static,
+    ) -> CompletionItemHook {
+        CompletionItemHook(Arc::new(hook))
+    }
+
+    pub(crate) fn apply(&self, item: &mut CompletionItem) {
+        (self.0)(item)
+    }
 }
 
+impl fmt::Debug for CompletionItemHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("CompletionItemHook")
+    }
+}
+
+impl PartialEq for CompletionItemHook {
+    fn eq(&self, other: &CompletionItemHook) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CompletionItemHook {}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SnippetCap {
     _private: (),
@@ -29,7 +176,32 @@ impl Default for CompletionConfig {
             enable_postfix_completions: true,
             add_call_parenthesis: true,
             add_call_argument_snippets: true,
+            call_parens_per_kind: CallParensPerKind::default(),
+            add_call_turbofish: false,
+            add_macro_braces: true,
+            stub_default_methods: true,
             snippet_cap: Some(SnippetCap { _private: () }),
+            report_cursor_offsets: false,
+            enable_autoimport_completions: false,
+            hide_unstable: false,
+            respect_doc_hidden: false,
+            deprioritize_prelude: false,
+            boost_referenced_items: false,
+            prefer_shorter_labels: false,
+            member_preference: MemberPreference::None,
+            explain_relevance: false,
+            show_enum_variant_count: false,
+            show_type_layout: false,
+            show_visibility: false,
+            show_async_in_labels: false,
+            show_item_origin: false,
+            show_self_kind: false,
+            show_const_eval: false,
+            show_method_chains: false,
+            min_score: None,
+            full_documentation: false,
+            documentation_format: DocumentationFormat::Markdown,
+            item_hook: None,
         }
     }
 }
@@ -1,20 +1,25 @@
 //! FIXME: write short doc here
 
-use hir::{Semantics, SemanticsScope, Type};
+use hir::{
+    BuiltinInt, BuiltinType, HasSource, PathResolution, ScopeDef, Semantics, SemanticsScope, Type,
+};
 use ra_db::SourceDatabase;
-use ra_ide_db::RootDatabase;
+use ra_ide_db::{helpers::FamousDefs, RootDatabase};
 use ra_syntax::{
     algo::{find_covering_element, find_node_at_offset},
-    ast, match_ast, AstNode, NodeOrToken,
+    ast::{self, NameOwner, TypeAscriptionOwner, TypeBoundsOwner, TypeParamsOwner},
+    match_ast, AstNode, Direction, NodeOrToken,
     SyntaxKind::*,
     SyntaxNode, SyntaxToken, TextRange, TextSize,
 };
 use ra_text_edit::Indel;
+use rustc_hash::FxHashSet;
 
 use super::patterns::{
-    has_bind_pat_parent, has_block_expr_parent, has_impl_as_prev_sibling, has_impl_parent,
-    has_item_list_or_source_file_parent, has_ref_parent, has_trait_as_prev_sibling,
-    has_trait_parent, if_is_prev, is_in_loop_body, is_match_arm, unsafe_is_prev,
+    break_or_continue_is_prev, enclosing_loop_labels, has_bind_pat_parent, has_block_expr_parent,
+    has_impl_as_prev_sibling, has_impl_parent, has_item_list_or_source_file_parent, has_ref_parent,
+    has_trait_as_prev_sibling, has_trait_parent, if_is_prev, is_in_loop_body, is_match_arm,
+    ref_type_amp_is_prev, unsafe_is_prev,
 };
 use crate::{call_info::ActiveParameter, completion::CompletionConfig, FilePosition};
 use test_utils::mark;
@@ -33,8 +38,24 @@ pub(crate) struct CompletionContext<'a> {
     pub(super) token: SyntaxToken,
     pub(super) krate: Option<hir::Crate>,
     pub(super) expected_type: Option<Type>,
+    /// The error type `E` when completing the argument of `Err(<|>)` in a
+    /// function returning `Result<_, E>`.
+    pub(super) expected_err_type: Option<Type>,
+    /// The `From` trait in scope, used to find values convertible to
+    /// `expected_err_type` or to a numeric `expected_type`.
+    pub(super) from_trait: Option<hir::Trait>,
+    /// When the function returns an `Option<T>` or `Result<T, _>`, the
+    /// constructor wrapping a `T` in it and `T`, like `Some` and `i32` in
+    /// `fn f() -> Option<i32> { <|> }`.
+    pub(super) expected_wrapper: Option<(&'static str, Type)>,
     pub(super) name_ref_syntax: Option<ast::NameRef>,
     pub(super) function_syntax: Option<ast::FnDef>,
+    /// Names referenced in the current file, other than the one we are
+    /// completing. Only collected with `CompletionConfig::boost_referenced_items`.
+    pub(super) referenced_names: FxHashSet<String>,
+    /// The items implicitly in scope through the prelude. Only collected with
+    /// `CompletionConfig::deprioritize_prelude`.
+    pub(super) prelude_defs: FxHashSet<hir::ModuleDef>,
     pub(super) use_item_syntax: Option<ast::UseItem>,
     pub(super) record_lit_syntax: Option<ast::RecordLit>,
     pub(super) record_pat_syntax: Option<ast::RecordPat>,
@@ -46,10 +67,19 @@ pub(crate) struct CompletionContext<'a> {
     /// If a name-binding or reference to a const in a pattern.
     /// Irrefutable patterns (like let) are excluded.
     pub(super) is_pat_binding_or_const: bool,
+    /// If the upper bound of a range pattern, like `1..=<|>`.
+    pub(super) is_range_pat_end: bool,
+    /// The type being matched against, if `is_range_pat_end`.
+    pub(super) range_pat_ty: Option<Type>,
     /// A single-indent path, like `foo`. `::foo` should not be considered a trivial path.
     pub(super) is_trivial_path: bool,
     /// If not a trivial path, the prefix (qualifier).
     pub(super) path_prefix: Option<hir::Path>,
+    /// If the prefix is a qualified self type, like `<S as Trait>::<|>`.
+    pub(super) is_qualified_self_path: bool,
+    /// The resolved self type of a qualified self type prefix, like `S` in
+    /// `<S as Trait>::<|>`.
+    pub(super) qualified_self_type: Option<Type>,
     pub(super) after_if: bool,
     /// `true` if we are a statement or a last expr in the block.
     pub(super) can_be_stmt: bool,
@@ -58,19 +88,73 @@ pub(crate) struct CompletionContext<'a> {
     /// The receiver if this is a field or method access, i.e. writing something.<|>
     pub(super) dot_receiver: Option<ast::Expr>,
     pub(super) dot_receiver_is_ambiguous_float_literal: bool,
+    /// The type expected for the whole field or method access, like `usize`
+    /// in `let n: usize = v.<|>`.
+    pub(super) dot_expected_type: Option<Type>,
+    /// The return type of the closure whose body we are completing, like
+    /// `u32` in `call(|| <|>)` for `fn call(f: impl Fn() -> u32)`.
+    pub(super) closure_ret_type: Option<Type>,
+    /// The parameter names for a closure passed as the argument we are
+    /// completing, like `p0` in `call(<|>)` for `fn call(f: impl Fn(i32))`.
+    /// `fn` pointer parameters may name their parameters, like
+    /// `fn(count: i32)`.
+    pub(super) closure_param_names: Option<Vec<String>>,
     /// If this is a call (method or function) in particular, i.e. the () are already there.
     pub(super) is_call: bool,
     /// If this is a macro call, i.e. the () are already there.
     pub(super) is_macro_call: bool,
+    /// If we are completing in the arguments of a macro call, like
+    /// `vec![V<|>]`.
+    pub(super) is_in_macro_args: bool,
     pub(super) is_path_type: bool,
+    /// The path whose generic arguments we are completing, like `Iterator` in
+    /// `Iterator<<|>>`.
+    pub(super) type_arg_list_path: Option<ast::Path>,
+    /// The const parameter whose argument we are completing, like `N` in
+    /// `Arr::<<|>>` for `struct Arr<const N: usize>`.
+    pub(super) const_param: Option<ast::ConstParam>,
+    /// The type of `const_param`, resolved where the parameter is declared.
+    const_param_ty: Option<Type>,
+    /// If we are completing the length of an array, like `[u8; <|>]`.
+    pub(super) is_array_len: bool,
+    /// If we are completing the type of a field in a struct definition, like
+    /// `struct S { x: <|> }`.
+    pub(super) is_field_def_type: bool,
+    /// If we are completing the type of an impl without `for`, like
+    /// `impl <|>`, which is most likely an inherent impl.
+    pub(super) is_inherent_impl_target: bool,
+    /// If we are completing the target type of a cast, like `x as <|>`.
+    pub(super) is_cast_target: bool,
+    /// If we are completing a trait bound, like `T: <|>`, `where T: <|>` or
+    /// `T: Display + <|>`.
+    pub(super) is_type_bound: bool,
+    /// If the bound is on a type parameter, like `T: <|>` or `where T: <|>`,
+    /// but not in a supertrait list or `impl Trait`. Only there can `?Sized`
+    /// relax the implicit `Sized` bound.
+    pub(super) is_type_param_bound: bool,
+    /// If we are completing a bound after the principal trait of a trait
+    /// object, like `dyn Trait + <|>`. Only auto traits and lifetimes may
+    /// follow there.
+    pub(super) is_dyn_trait_extra_bound: bool,
+    /// The name of the trait if we are completing a bound on `Self` in its
+    /// where clause, like `Shape` in `trait Shape where Self: <|>`. Such bounds
+    /// are supertraits.
+    pub(super) self_bound_trait: Option<String>,
+    /// The enum variant if we are completing its discriminant, like
+    /// `enum E { A = <|> }`. This node is from the file with the fake ident.
+    pub(super) enum_discriminant: Option<ast::EnumVariant>,
     pub(super) has_type_args: bool,
     pub(super) attribute_under_caret: Option<ast::Attr>,
     pub(super) unsafe_is_prev: bool,
     pub(super) if_is_prev: bool,
+    /// If we are right after `&` in a reference type, like `&<|> str`.
+    pub(super) ref_type_amp_is_prev: bool,
     pub(super) block_expr_parent: bool,
     pub(super) bind_pat_parent: bool,
     pub(super) ref_pat_parent: bool,
     pub(super) in_loop_body: bool,
+    /// Labels of the enclosing loops, if we are right after `break` or `continue`.
+    pub(super) loop_labels: Vec<String>,
     pub(super) has_trait_parent: bool,
     pub(super) has_impl_parent: bool,
     pub(super) trait_as_prev_sibling: bool,
@@ -113,8 +197,13 @@ impl<'a> CompletionContext<'a> {
             offset: position.offset,
             krate,
             expected_type: None,
+            expected_err_type: None,
+            from_trait: None,
+            expected_wrapper: None,
             name_ref_syntax: None,
             function_syntax: None,
+            referenced_names: FxHashSet::default(),
+            prelude_defs: FxHashSet::default(),
             use_item_syntax: None,
             record_lit_syntax: None,
             record_pat_syntax: None,
@@ -123,20 +212,41 @@ impl<'a> CompletionContext<'a> {
             active_parameter: ActiveParameter::at(db, position),
             is_param: false,
             is_pat_binding_or_const: false,
+            is_range_pat_end: false,
+            range_pat_ty: None,
             is_trivial_path: false,
             path_prefix: None,
+            is_qualified_self_path: false,
+            qualified_self_type: None,
             after_if: false,
             can_be_stmt: false,
             is_new_item: false,
             dot_receiver: None,
+            dot_expected_type: None,
+            closure_ret_type: None,
+            closure_param_names: None,
             is_call: false,
             is_macro_call: false,
+            is_in_macro_args: false,
             is_path_type: false,
+            type_arg_list_path: None,
+            const_param: None,
+            const_param_ty: None,
+            is_field_def_type: false,
+            is_inherent_impl_target: false,
+            is_cast_target: false,
+            is_type_bound: false,
+            is_type_param_bound: false,
+            is_dyn_trait_extra_bound: false,
+            self_bound_trait: None,
+            is_array_len: false,
+            enum_discriminant: None,
             has_type_args: false,
             dot_receiver_is_ambiguous_float_literal: false,
             attribute_under_caret: None,
             unsafe_is_prev: false,
             in_loop_body: false,
+            loop_labels: Vec::new(),
             ref_pat_parent: false,
             bind_pat_parent: false,
             block_expr_parent: false,
@@ -145,10 +255,18 @@ impl<'a> CompletionContext<'a> {
             trait_as_prev_sibling: false,
             impl_as_prev_sibling: false,
             if_is_prev: false,
+            ref_type_amp_is_prev: false,
             is_match_arm: false,
             has_item_list_or_source_file_parent: false,
         };
 
+        if config.boost_referenced_items {
+            ctx.referenced_names = referenced_names(original_file.syntax(), position.offset);
+        }
+        if config.deprioritize_prelude {
+            ctx.prelude_defs = prelude_defs(db, ctx.krate);
+        }
+
         let mut original_file = original_file.syntax().clone();
         let mut hypothetical_file = file_with_fake_ident.syntax().clone();
         let mut offset = position.offset;
@@ -213,19 +331,182 @@ impl<'a> CompletionContext<'a> {
         }
     }
 
+    /// The part of the identifier being completed before the cursor, like
+    /// `mF` in `s.mF<|>`.
+    pub(crate) fn typed_text(&self) -> &str {
+        if self.token.kind() != IDENT {
+            return "";
+        }
+        let range = self.original_token.text_range();
+        let len = self.offset.min(range.end()) - range.start();
+        &self.original_token.text()[..usize::from(len)]
+    }
+
     pub(crate) fn scope(&self) -> SemanticsScope<'_> {
         self.sema.scope_at_offset(&self.token.parent(), self.offset)
     }
 
+    /// Whether we are inside an `async fn`, where `.await` can be used.
+    pub(crate) fn is_in_async_fn(&self) -> bool {
+        self.function_syntax.as_ref().map_or(false, |it| it.async_token().is_some())
+    }
+
+    /// Whether we are inside a function returning `Result` or `Option`, where
+    /// `?` can be used.
+    pub(crate) fn is_in_fallible_fn(&self) -> bool {
+        let func = self.function_syntax.as_ref().and_then(|it| self.sema.to_def(it));
+        func.map_or(false, |it| self.returns_result_or_option(it))
+    }
+
+    /// Whether `func` returns `Result` or `Option`, after awaiting it if it is
+    /// an `async fn`.
+    pub(crate) fn returns_result_or_option(&self, func: hir::Function) -> bool {
+        let ret_type = func.ret_type(self.db);
+        let ret_type = ret_type.future_output(self.db).unwrap_or(ret_type);
+        self.is_result_or_option(&ret_type)
+    }
+
+    /// Whether `ty` is `core::result::Result` or `core::option::Option`.
+    pub(crate) fn is_result_or_option(&self, ty: &Type) -> bool {
+        let famous_defs = match self.famous_defs() {
+            Some(it) => it,
+            None => return false,
+        };
+        match ty.as_adt() {
+            Some(hir::Adt::Enum(it)) => {
+                Some(it) == famous_defs.core_result_Result()
+                    || Some(it) == famous_defs.core_option_Option()
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn famous_defs(&self) -> Option<FamousDefs<'_, 'a>> {
+        self.krate.map(|krate| FamousDefs(&self.sema, krate))
+    }
+
+    /// Whether there is a type to score completion items against, see
+    /// `compute_score`.
+    pub(crate) fn has_type_context(&self) -> bool {
+        self.record_field_syntax.is_some()
+            || self.expected_err_type.is_some()
+            || self.active_parameter.is_some()
+            || self.dot_expected_type.is_some()
+            || self.closure_ret_type.is_some()
+    }
+
+    /// Whether this is a field or method access on `self`, like `self.<|>`.
+    pub(crate) fn is_self_receiver(&self) -> bool {
+        match &self.dot_receiver {
+            Some(ast::Expr::PathExpr(it)) => {
+                it.path().map_or(false, |it| it.syntax().text() == "self")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this is a field or method access on a binding which can't be
+    /// borrowed mutably, like `v` in `let v = Vec::new(); v.<|>`.
+    pub(crate) fn is_immutable_receiver(&self) -> bool {
+        let path = match &self.dot_receiver {
+            Some(ast::Expr::PathExpr(it)) => it.path(),
+            _ => None,
+        };
+        match path.and_then(|it| self.sema.resolve_path(&it)) {
+            Some(PathResolution::Local(local)) => {
+                !local.is_mut(self.db) && !local.ty(self.db).is_mutable_reference()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether we are inside the initializer of a `const` or `static` item.
+    pub(crate) fn is_in_const_context(&self) -> bool {
+        if self.enum_discriminant.is_some() || self.const_param.is_some() || self.is_array_len {
+            return true;
+        }
+        self.token
+            .parent()
+            .ancestors()
+            .take_while(|it| !ast::FnDef::can_cast(it.kind()))
+            .any(|it| ast::ConstDef::can_cast(it.kind()) || ast::StaticDef::can_cast(it.kind()))
+    }
+
+    /// The type of the constant we are completing, like `usize` for an array
+    /// length or the type of the const parameter we are completing an argument
+    /// for.
+    pub(crate) fn expected_const_type(&self) -> Option<Type> {
+        if self.is_array_len {
+            let usize = BuiltinType::Int(BuiltinInt::USIZE);
+            return Some(Type::builtin(self.db, self.scope().module()?, usize));
+        }
+        self.const_param_ty.clone()
+    }
+
+    /// Finds the const parameter corresponding to the generic argument `path`,
+    /// counting only type and const arguments, like the compiler does. Also
+    /// returns the module the parameter is declared in.
+    fn const_param_at(&self, path: &ast::Path) -> Option<(ast::ConstParam, hir::Module)> {
+        let type_arg = path.syntax().parent()?.parent().and_then(ast::TypeArg::cast)?;
+        let type_arg_list = type_arg.syntax().parent().and_then(ast::TypeArgList::cast)?;
+        let idx = type_arg_list
+            .generic_args()
+            .filter(|it| matches!(it, ast::GenericArg::TypeArg(_) | ast::GenericArg::ConstArg(_)))
+            .position(|it| it.syntax() == type_arg.syntax())?;
+
+        let (type_param_list, module) =
+            match self.sema.resolve_path(self.type_arg_list_path.as_ref()?)? {
+                PathResolution::Def(hir::ModuleDef::Adt(hir::Adt::Struct(it))) => {
+                    (it.source(self.db).value.type_param_list(), it.module(self.db))
+                }
+                PathResolution::Def(hir::ModuleDef::Adt(hir::Adt::Enum(it))) => {
+                    (it.source(self.db).value.type_param_list(), it.module(self.db))
+                }
+                PathResolution::Def(hir::ModuleDef::Adt(hir::Adt::Union(it))) => {
+                    (it.source(self.db).value.type_param_list(), it.module(self.db))
+                }
+                PathResolution::Def(hir::ModuleDef::Function(it)) => {
+                    (it.source(self.db).value.type_param_list(), it.module(self.db))
+                }
+                _ => return None,
+            };
+        match type_param_list?
+            .generic_params()
+            .filter(|it| !matches!(it, ast::GenericParam::LifetimeParam(_)))
+            .nth(idx)?
+        {
+            ast::GenericParam::ConstParam(it) => Some((it, module)),
+            _ => None,
+        }
+    }
+
+    /// The type of the scrutinee, if `pat` is the pattern of a match arm.
+    ///
+    /// FIXME: this should use the type of `pat` itself, but range patterns are
+    /// not lowered yet.
+    fn match_scrutinee_ty(&self, original_file: &SyntaxNode, pat: &ast::RangePat) -> Option<Type> {
+        let arm = pat.syntax().parent().and_then(ast::MatchArm::cast)?;
+        let match_expr = arm.syntax().ancestors().find_map(ast::MatchExpr::cast)?;
+        let scrutinee = match_expr.expr()?;
+        // The scrutinee precedes the fake ident, so it has the same range.
+        let scrutinee =
+            find_node_with_range::<ast::Expr>(original_file, scrutinee.syntax().text_range())?;
+        self.sema.type_of_expr(&scrutinee)
+    }
+
     fn fill_keyword_patterns(&mut self, file_with_fake_ident: &SyntaxNode, offset: TextSize) {
         let fake_ident_token = file_with_fake_ident.token_at_offset(offset).right_biased().unwrap();
         let syntax_element = NodeOrToken::Token(fake_ident_token.clone());
         self.block_expr_parent = has_block_expr_parent(syntax_element.clone());
         self.unsafe_is_prev = unsafe_is_prev(syntax_element.clone());
         self.if_is_prev = if_is_prev(syntax_element.clone());
+        self.ref_type_amp_is_prev = ref_type_amp_is_prev(syntax_element.clone());
         self.bind_pat_parent = has_bind_pat_parent(syntax_element.clone());
         self.ref_pat_parent = has_ref_parent(syntax_element.clone());
         self.in_loop_body = is_in_loop_body(syntax_element.clone());
+        if break_or_continue_is_prev(syntax_element.clone()) {
+            self.loop_labels = enclosing_loop_labels(syntax_element.clone());
+        }
         self.has_trait_parent = has_trait_parent(syntax_element.clone());
         self.has_impl_parent = has_impl_parent(syntax_element.clone());
         self.impl_as_prev_sibling = has_impl_as_prev_sibling(syntax_element.clone());
@@ -258,7 +539,23 @@ impl<'a> CompletionContext<'a> {
                 Some(ty)
             })
             .flatten();
+        self.expected_err_type = self.find_expected_err_type(offset);
+        self.closure_ret_type =
+            self.find_closure_ret_type(original_file, &file_with_fake_ident, offset);
+        self.closure_param_names =
+            self.find_closure_param_names(original_file, &file_with_fake_ident, offset);
+        let expects_number = self.expected_type.as_ref().map_or(false, is_numeric_type);
+        if self.expected_err_type.is_some() || expects_number {
+            self.from_trait = self.famous_defs().and_then(|it| it.core_convert_From());
+        }
         self.attribute_under_caret = find_node_at_offset(&file_with_fake_ident, offset);
+        self.is_in_macro_args = self
+            .original_token
+            .parent()
+            .ancestors()
+            .filter_map(ast::TokenTree::cast)
+            .filter_map(|it| it.syntax().parent())
+            .any(|it| ast::MacroCall::can_cast(it.kind()));
 
         // First, let's try to complete a reference to some declaration.
         if let Some(name_ref) = find_node_at_offset::<ast::NameRef>(&file_with_fake_ident, offset) {
@@ -290,6 +587,13 @@ impl<'a> CompletionContext<'a> {
                 if bind_pat.syntax().parent().and_then(ast::RecordFieldPatList::cast).is_some() {
                     self.is_pat_binding_or_const = false;
                 }
+                // FIXME: also handle the lower bound, like `<|>..=10`.
+                if let Some(range_pat) = bind_pat.syntax().parent().and_then(ast::RangePat::cast) {
+                    if range_pat.end().map_or(false, |it| it.syntax() == bind_pat.syntax()) {
+                        self.is_range_pat_end = true;
+                        self.range_pat_ty = self.match_scrutinee_ty(original_file, &range_pat);
+                    }
+                }
                 if let Some(let_stmt) = bind_pat.syntax().ancestors().find_map(ast::LetStmt::cast) {
                     if let Some(pat) = let_stmt.pat() {
                         if pat.syntax().text_range().contains_range(bind_pat.syntax().text_range())
@@ -311,6 +615,202 @@ impl<'a> CompletionContext<'a> {
         }
     }
 
+    fn find_closure_ret_type(
+        &self,
+        original_file: &SyntaxNode,
+        file_with_fake_ident: &SyntaxNode,
+        offset: TextSize,
+    ) -> Option<Type> {
+        let closure: ast::LambdaExpr = find_node_at_offset(file_with_fake_ident, offset)?;
+        // Only the tail expression of the body, not something nested inside of it.
+        let tail = match closure.body()? {
+            ast::Expr::BlockExpr(block) => block.expr()?,
+            body => body,
+        };
+        if !matches!(&tail, ast::Expr::PathExpr(it) if it.syntax().text_range().contains(offset)) {
+            return None;
+        }
+
+        let closure: ast::LambdaExpr =
+            find_node_at_offset(original_file, closure.syntax().text_range().start())?;
+        let arg_list = closure.syntax().parent().and_then(ast::ArgList::cast)?;
+        let idx = arg_list.args().position(|it| it.syntax() == closure.syntax())?;
+        let (_, ret_type, module) = self.expected_fn_signature(&arg_list, idx)?;
+        self.resolve_type_ref(&self.sema.scope_for_module(module), &ret_type?.type_ref()?)
+    }
+
+    fn find_closure_param_names(
+        &self,
+        original_file: &SyntaxNode,
+        file_with_fake_ident: &SyntaxNode,
+        offset: TextSize,
+    ) -> Option<Vec<String>> {
+        let path_expr: ast::PathExpr = find_node_at_offset(file_with_fake_ident, offset)?;
+        let arg_list = path_expr.syntax().parent().and_then(ast::ArgList::cast)?;
+        let idx = arg_list.args().position(|it| it.syntax() == path_expr.syntax())?;
+        let arg_list: ast::ArgList =
+            find_node_at_offset(original_file, arg_list.syntax().text_range().start())?;
+        let (params, ..) = self.expected_fn_signature(&arg_list, idx)?;
+        let names = params.params().enumerate().map(|(idx, param)| {
+            match param.pat() {
+                Some(ast::Pat::BindPat(it)) => it.name().map(|it| it.text().to_string()),
+                _ => None,
+            }
+            .unwrap_or_else(|| format!("p{}", idx))
+        });
+        Some(names.collect())
+    }
+
+    /// The parameter list and return type of the `Fn` bound or `fn` pointer
+    /// expected for the `idx`th argument of a call, like `(i32)` and `-> u32`
+    /// for `fn call(f: impl Fn(i32) -> u32)`, along with the module of the
+    /// callee, in which their types are to be resolved.
+    fn expected_fn_signature(
+        &self,
+        arg_list: &ast::ArgList,
+        idx: usize,
+    ) -> Option<(ast::ParamList, Option<ast::RetType>, hir::Module)> {
+        let callee = match arg_list.syntax().parent().and_then(ast::Expr::cast)? {
+            ast::Expr::CallExpr(call) => match call.expr()? {
+                ast::Expr::PathExpr(it) => match self.sema.resolve_path(&it.path()?)? {
+                    PathResolution::Def(hir::ModuleDef::Function(it)) => it,
+                    _ => return None,
+                },
+                _ => return None,
+            },
+            ast::Expr::MethodCallExpr(call) => self.sema.resolve_method_call(&call)?,
+            _ => return None,
+        };
+
+        // FIXME: this should look at the inferred signature of the closure
+        // instead of the syntax of the parameter.
+        let module = callee.module(self.db);
+        let fn_def = callee.source(self.db).value;
+        let param_ty = fn_def.param_list()?.params().nth(idx)?.ascribed_type()?;
+        let bounds: Vec<ast::TypeBound> = match &param_ty {
+            ast::TypeRef::FnPointerType(it) => {
+                return Some((it.param_list()?, it.ret_type(), module))
+            }
+            ast::TypeRef::ImplTraitType(it) => it.type_bound_list()?.bounds().collect(),
+            ast::TypeRef::PathType(it) => {
+                let name = it.path()?.segment()?.name_ref()?.text().clone();
+                let type_param = fn_def
+                    .type_param_list()?
+                    .type_params()
+                    .find(|it| it.name().map_or(false, |it| it.text() == &name))?;
+                let where_preds = fn_def.where_clause().into_iter().flat_map(|it| it.predicates());
+                type_param
+                    .type_bound_list()
+                    .into_iter()
+                    .chain(
+                        where_preds
+                            .filter(|it| {
+                                it.type_ref()
+                                    .map_or(false, |it| it.syntax().text() == name.as_str())
+                            })
+                            .filter_map(|it| it.type_bound_list()),
+                    )
+                    .flat_map(|it| it.bounds())
+                    .collect()
+            }
+            _ => return None,
+        };
+        bounds.into_iter().find_map(|bound| {
+            let segment = match bound.type_ref()? {
+                ast::TypeRef::PathType(it) => it.path()?.segment()?,
+                _ => return None,
+            };
+            if !matches!(segment.name_ref()?.text().as_str(), "Fn" | "FnMut" | "FnOnce") {
+                return None;
+            }
+            Some((segment.param_list()?, segment.ret_type(), module))
+        })
+    }
+
+    fn find_expected_wrapper(&self, path: &ast::Path) -> Option<(&'static str, Type)> {
+        let path_expr = path.syntax().parent().and_then(ast::PathExpr::cast)?;
+        let parent = path_expr.syntax().parent()?;
+        let is_returned = ast::ReturnExpr::can_cast(parent.kind())
+            || ast::BlockExpr::cast(parent).map_or(false, |block| {
+                block.syntax().parent().map_or(false, |it| ast::FnDef::can_cast(it.kind()))
+            });
+        if !is_returned {
+            return None;
+        }
+        let fn_def = self
+            .sema
+            .ancestors_with_macros(self.original_token.parent())
+            .find_map(ast::FnDef::cast)?;
+        let ret_ty = self.sema.to_def(&fn_def)?.ret_type(self.db);
+        let famous_defs = self.famous_defs()?;
+        let wrapper = match ret_ty.as_adt()? {
+            hir::Adt::Enum(it) if Some(it) == famous_defs.core_option_Option() => "Some",
+            hir::Adt::Enum(it) if Some(it) == famous_defs.core_result_Result() => "Ok",
+            _ => return None,
+        };
+        let inner = ret_ty.type_arguments().into_iter().next()?;
+        Some((wrapper, inner))
+    }
+
+    /// Resolves a type written as a path in `scope`, like `S` in
+    /// `<S as Trait>::<|>`.
+    fn resolve_type_ref(&self, scope: &SemanticsScope, type_ref: &ast::TypeRef) -> Option<Type> {
+        let path = match type_ref {
+            ast::TypeRef::PathType(it) => it.path()?,
+            _ => return None,
+        };
+        #[allow(deprecated)]
+        let path = hir::Path::from_ast(path)?;
+        match scope.resolve_hir_path(&path)? {
+            PathResolution::Def(hir::ModuleDef::Adt(it)) => Some(it.ty(self.db)),
+            PathResolution::Def(hir::ModuleDef::BuiltinType(it)) => {
+                Some(Type::builtin(self.db, scope.module()?, it))
+            }
+            PathResolution::Def(hir::ModuleDef::TypeAlias(it)) => Some(it.ty(self.db)),
+            PathResolution::SelfType(it) => Some(it.target_ty(self.db)),
+            _ => None,
+        }
+    }
+
+    fn find_expected_err_type(&self, offset: TextSize) -> Option<Type> {
+        let arg_list = self.token.ancestors().find_map(ast::ArgList::cast)?;
+        // Only the argument itself, not something nested inside of it.
+        let arg = arg_list.args().find(|it| it.syntax().text_range().contains_inclusive(offset));
+        if !matches!(arg, None | Some(ast::Expr::PathExpr(_))) {
+            return None;
+        }
+        let call = arg_list.syntax().parent().and_then(ast::CallExpr::cast)?;
+        let callee = match call.expr()? {
+            ast::Expr::PathExpr(it) => it.path()?,
+            _ => return None,
+        };
+        let result = self.famous_defs()?.core_result_Result()?;
+        match self.sema.resolve_path(&callee)? {
+            PathResolution::Def(hir::ModuleDef::EnumVariant(it))
+                if it.parent_enum(self.db) == result && it.name(self.db).to_string() == "Err" => {}
+            _ => return None,
+        }
+
+        let fn_def = self.token.ancestors().find_map(ast::FnDef::cast)?;
+        if self.sema.to_def(&fn_def)?.ret_type(self.db).as_adt() != Some(hir::Adt::Enum(result)) {
+            return None;
+        }
+        // FIXME: this should take the error type from the return type instead
+        // of resolving its syntax.
+        let ret_segment = match fn_def.ret_type()?.type_ref()? {
+            ast::TypeRef::PathType(it) => it.path()?.segment()?,
+            _ => return None,
+        };
+        let err_path = match ret_segment.type_arg_list()?.type_args().nth(1)?.type_ref()? {
+            ast::TypeRef::PathType(it) => it.path()?,
+            _ => return None,
+        };
+        match self.sema.resolve_path(&err_path)? {
+            PathResolution::Def(hir::ModuleDef::Adt(adt)) => Some(adt.ty(self.db)),
+            _ => None,
+        }
+    }
+
     fn classify_name_ref(
         &mut self,
         original_file: &SyntaxNode,
@@ -349,6 +849,13 @@ impl<'a> CompletionContext<'a> {
         self.use_item_syntax =
             self.sema.ancestors_with_macros(self.token.parent()).find_map(ast::UseItem::cast);
 
+        self.enum_discriminant =
+            name_ref.syntax().ancestors().find_map(ast::EnumVariant::cast).filter(|variant| {
+                variant
+                    .expr()
+                    .map_or(false, |it| it.syntax().text_range().contains_range(name_range))
+            });
+
         self.function_syntax = self
             .sema
             .ancestors_with_macros(self.token.parent())
@@ -377,10 +884,111 @@ impl<'a> CompletionContext<'a> {
                 .and_then(|it| it.syntax().parent().and_then(ast::CallExpr::cast))
                 .is_some();
             self.is_macro_call = path.syntax().parent().and_then(ast::MacroCall::cast).is_some();
+            self.is_array_len = path
+                .syntax()
+                .parent()
+                .and_then(ast::PathExpr::cast)
+                .map_or(false, |it| is_array_len(&ast::Expr::PathExpr(it)));
+            self.expected_wrapper = self.find_expected_wrapper(&path);
 
             self.is_path_type = path.syntax().parent().and_then(ast::PathType::cast).is_some();
+            if self.is_path_type {
+                self.is_field_def_type =
+                    path.syntax().parent().and_then(|it| it.parent()).map_or(false, |it| {
+                        ast::RecordFieldDef::can_cast(it.kind())
+                            || ast::TupleFieldDef::can_cast(it.kind())
+                    });
+                self.is_inherent_impl_target = path
+                    .syntax()
+                    .parent()
+                    .and_then(|it| it.parent())
+                    .and_then(ast::ImplDef::cast)
+                    .map_or(false, |it| it.for_token().is_none());
+                self.is_cast_target = path
+                    .syntax()
+                    .parent()
+                    .and_then(|it| it.parent())
+                    .map_or(false, |it| ast::CastExpr::can_cast(it.kind()));
+                self.is_type_bound = path
+                    .syntax()
+                    .parent()
+                    .and_then(|it| it.parent())
+                    .map_or(false, |it| ast::TypeBound::can_cast(it.kind()));
+                self.self_bound_trait = if self.is_type_bound {
+                    path.syntax()
+                        .ancestors()
+                        .find_map(ast::WherePred::cast)
+                        .filter(|it| it.type_ref().map_or(false, |it| it.syntax().text() == "Self"))
+                        .and_then(|it| it.syntax().ancestors().find_map(ast::TraitDef::cast))
+                        .and_then(|it| it.name())
+                        .map(|it| it.text().to_string())
+                } else {
+                    None
+                };
+                // `?Sized` isn't allowed on `Self` in traits.
+                self.is_type_param_bound = self.is_type_bound
+                    && self.self_bound_trait.is_none()
+                    && path
+                        .syntax()
+                        .ancestors()
+                        .find_map(ast::TypeBoundList::cast)
+                        .and_then(|it| it.syntax().parent())
+                        .map_or(false, |it| {
+                            ast::TypeParam::can_cast(it.kind())
+                                || ast::WherePred::can_cast(it.kind())
+                        });
+                self.is_dyn_trait_extra_bound = self.is_type_bound
+                    && path
+                        .syntax()
+                        .ancestors()
+                        .find_map(ast::TypeBound::cast)
+                        .filter(|it| {
+                            it.syntax().parent().map_or(false, |it| {
+                                it.parent()
+                                    .map_or(false, |it| ast::DynTraitType::can_cast(it.kind()))
+                            })
+                        })
+                        .map_or(false, |it| {
+                            it.syntax()
+                                .siblings(Direction::Prev)
+                                .skip(1)
+                                .any(|it| ast::TypeBound::can_cast(it.kind()))
+                        });
+                let is_type_arg = path
+                    .syntax()
+                    .parent()
+                    .and_then(|it| it.parent())
+                    .and_then(ast::TypeArg::cast)
+                    .is_some();
+                if is_type_arg {
+                    self.type_arg_list_path = self
+                        .sema
+                        .find_node_at_offset_with_macros::<ast::TypeArgList>(original_file, offset)
+                        .and_then(|it| it.syntax().parent())
+                        .and_then(ast::PathSegment::cast)
+                        .map(|it| it.parent_path());
+                    if let Some((const_param, module)) = self.const_param_at(&path) {
+                        let scope = self.sema.scope_for_module(module);
+                        self.const_param_ty = const_param
+                            .ascribed_type()
+                            .and_then(|it| self.resolve_type_ref(&scope, &it));
+                        self.const_param = Some(const_param);
+                    }
+                }
+            }
             self.has_type_args = segment.type_arg_list().is_some();
 
+            let qualified_self_type_ref =
+                path.qualifier().and_then(|it| it.segment()).and_then(|it| it.kind()).and_then(
+                    |it| match it {
+                        ast::PathSegmentKind::Type { type_ref, trait_ref: Some(_) } => type_ref,
+                        _ => None,
+                    },
+                );
+            self.is_qualified_self_path = qualified_self_type_ref.is_some();
+            self.qualified_self_type =
+                qualified_self_type_ref.and_then(|it| self.resolve_type_ref(&self.scope(), &it));
+
             #[allow(deprecated)]
             if let Some(path) = hir::Path::from_ast(path.clone()) {
                 if let Some(path_prefix) = path.qualifier() {
@@ -451,6 +1059,60 @@ impl<'a> CompletionContext<'a> {
                 .and_then(|r| find_node_with_range(original_file, r));
             self.is_call = true;
         }
+        if let Some(receiver) = &self.dot_receiver {
+            // `42.` is a single literal token in the original file, so the
+            // let statement may be the direct parent of the receiver.
+            self.dot_expected_type = receiver
+                .syntax()
+                .ancestors()
+                .skip(1)
+                .take(2)
+                .find_map(ast::LetStmt::cast)
+                .filter(|it| it.ascribed_type().is_some())
+                .and_then(|it| it.pat())
+                .and_then(|it| self.sema.type_of_pat(&it));
+        }
+    }
+}
+
+fn prelude_defs(db: &RootDatabase, krate: Option<hir::Crate>) -> FxHashSet<hir::ModuleDef> {
+    let prelude = match krate.and_then(|it| it.prelude(db)) {
+        Some(it) => it,
+        None => return FxHashSet::default(),
+    };
+    prelude
+        .scope(db, None)
+        .into_iter()
+        .filter_map(|(_, it)| match it {
+            ScopeDef::ModuleDef(it) => Some(it),
+            _ => None,
+        })
+        .collect()
+}
+
+fn referenced_names(file: &SyntaxNode, offset: TextSize) -> FxHashSet<String> {
+    file.descendants()
+        .filter_map(ast::NameRef::cast)
+        .filter(|it| !it.syntax().text_range().contains_inclusive(offset))
+        .map(|it| it.text().to_string())
+        .collect()
+}
+
+/// Checks that `expr` is the length of an array type or repeat expression,
+/// like `N` in `[u8; N]` or `[0; N]`.
+fn is_array_len(expr: &ast::Expr) -> bool {
+    match expr.syntax().parent() {
+        Some(parent) => match_ast! {
+            match parent {
+                ast::ArrayType(it) => it.expr().as_ref() == Some(expr),
+                ast::ArrayExpr(it) => match it.kind() {
+                    ast::ArrayExprKind::Repeat { repeat, .. } => repeat.as_ref() == Some(expr),
+                    ast::ArrayExprKind::ElementList(_) => false,
+                },
+                _ => false,
+            }
+        },
+        None => false,
     }
 }
 
@@ -464,3 +1126,7 @@ fn is_node<N: AstNode>(node: &SyntaxNode) -> bool {
         Some(n) => n.syntax().text_range() == node.text_range(),
     }
 }
+
+pub(super) fn is_numeric_type(ty: &Type) -> bool {
+    matches!(ty.as_builtin(), Some(BuiltinType::Int(_)) | Some(BuiltinType::Float(_)))
+}
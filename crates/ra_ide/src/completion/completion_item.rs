@@ -3,10 +3,13 @@
 use std::fmt;
 
 use hir::Documentation;
-use ra_syntax::TextRange;
+use ra_syntax::{TextRange, TextSize};
 use ra_text_edit::TextEdit;
+use stdx::format_to;
 
-use crate::completion::completion_config::SnippetCap;
+use crate::completion::completion_config::{
+    CompletionConfig, CompletionItemHook, MemberPreference, SnippetCap,
+};
 
 /// `CompletionItem` describes a single completion variant in the editor pop-up.
 /// It is basically a POD with various properties. To construct a
@@ -46,6 +49,10 @@ pub struct CompletionItem {
     /// Additional info to show in the UI pop up.
     detail: Option<String>,
     documentation: Option<Documentation>,
+    /// Whether `documentation` is only the summary of the documentation of
+    /// the item. The full text is available through
+    /// `Analysis::completion_documentation`.
+    has_more_documentation: bool,
 
     /// Whether this item is marked as deprecated
     deprecated: bool,
@@ -54,8 +61,46 @@ pub struct CompletionItem {
     /// after completion.
     trigger_call_info: bool,
 
+    /// Ask the editor to complete again after inserting this item, like after
+    /// the `dyn ` prefix of a type.
+    trigger_completion: bool,
+
     /// Score is useful to pre select or display in better order completion items
     score: Option<CompletionScore>,
+
+    /// The `use` item added along with this item, which is part of `text_edit`
+    /// as well. Exposed separately so that clients can batch imports.
+    import_edit: Option<ImportEdit>,
+    /// Where to put the cursor after inserting a plain text item, relative to
+    /// the start of the inserted text. Only set with
+    /// `CompletionConfig::report_cursor_offsets`.
+    cursor_offset: Option<TextSize>,
+    /// Whether this item should be sorted after items of similar relevance,
+    /// like prelude items with `CompletionConfig::deprioritize_prelude`.
+    deprioritized: bool,
+
+    /// Whether the item is referenced elsewhere in the current file, with
+    /// `CompletionConfig::boost_referenced_items`. Such items are sorted
+    /// before items of similar relevance.
+    referenced_nearby: bool,
+
+    /// Whether this is a field or method of `self`, like `self.width`. Such
+    /// items are sorted after items referenced nearby, but before the rest.
+    self_member: bool,
+
+    /// Whether the typed text matches the start of the words of the item's
+    /// name, like `mF` for `my_field`. Such items are sorted after `self`
+    /// members, but before the rest.
+    word_match: bool,
+
+    /// How many modules away from the completion site an item is defined,
+    /// counting the steps up and down the module tree. Breaks ties between
+    /// items of the same relevance, so nearby items sort first.
+    module_distance: Option<u32>,
+
+    /// Why this item was ranked the way it was, only computed when
+    /// `CompletionConfig::explain_relevance` is set.
+    relevance: Option<CompletionRelevance>,
 }
 
 // We use custom debug for CompletionItem to make `insta`'s diffs more readable.
@@ -70,6 +115,12 @@ impl fmt::Debug for CompletionItem {
         } else {
             s.field("text_edit", &self.text_edit);
         }
+        if let Some(import_edit) = &self.import_edit {
+            s.field("import", &import_edit.import_path);
+        }
+        if let Some(cursor_offset) = self.cursor_offset {
+            s.field("cursor_offset", &cursor_offset);
+        }
         if let Some(kind) = self.kind().as_ref() {
             s.field("kind", kind);
         }
@@ -82,20 +133,45 @@ impl fmt::Debug for CompletionItem {
         if let Some(documentation) = self.documentation() {
             s.field("documentation", &documentation);
         }
+        if self.has_more_documentation {
+            s.field("has_more_documentation", &true);
+        }
         if self.deprecated {
             s.field("deprecated", &true);
         }
         if let Some(score) = &self.score {
             s.field("score", score);
         }
+        if self.deprioritized {
+            s.field("deprioritized", &true);
+        }
+        if self.referenced_nearby {
+            s.field("referenced_nearby", &true);
+        }
+        if let Some(relevance) = &self.relevance {
+            s.field("relevance", relevance);
+        }
         if self.trigger_call_info {
             s.field("trigger_call_info", &true);
         }
+        if self.trigger_completion {
+            s.field("trigger_completion", &true);
+        }
         s.finish()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// An import which a completion item adds, like `use ext::Shout;` for a
+/// method of the `Shout` trait.
+#[derive(Debug, Clone)]
+pub struct ImportEdit {
+    /// The imported path, like `ext::Shout`.
+    pub import_path: String,
+    /// Inserts the `use` item.
+    pub edit: TextEdit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CompletionScore {
     /// If only type match
     TypeMatch,
@@ -103,6 +179,30 @@ pub enum CompletionScore {
     TypeAndNameMatch,
 }
 
+/// A breakdown of the signals which went into ranking a completion item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompletionRelevance {
+    /// The type of the item matches the expected type.
+    pub type_match: bool,
+    /// The name of the item matches the expected name.
+    pub name_match: bool,
+    /// The item is a local binding.
+    pub is_local: bool,
+    /// The item is marked as deprecated.
+    pub is_deprecated: bool,
+}
+
+impl CompletionRelevance {
+    pub(crate) fn new(score: Option<CompletionScore>) -> CompletionRelevance {
+        let (type_match, name_match) = match score {
+            None => (false, false),
+            Some(CompletionScore::TypeMatch) => (true, false),
+            Some(CompletionScore::TypeAndNameMatch) => (true, true),
+        };
+        CompletionRelevance { type_match, name_match, ..CompletionRelevance::default() }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompletionItemKind {
     Snippet,
@@ -189,15 +289,27 @@ impl CompletionItem {
             lookup: None,
             kind: None,
             text_edit: None,
+            import_edit: None,
+            cursor_offset: None,
             deprecated: None,
             trigger_call_info: None,
+            trigger_completion: None,
             score: None,
+            deprioritized: None,
+            referenced_nearby: None,
+            self_member: None,
+            word_match: None,
+            module_distance: None,
+            relevance: None,
         }
     }
     /// What user sees in pop-up in the UI.
     pub fn label(&self) -> &str {
         &self.label
     }
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
     pub fn source_range(&self) -> TextRange {
         self.source_range
     }
@@ -206,18 +318,37 @@ impl CompletionItem {
         self.insert_text_format
     }
 
+    pub fn import_edit(&self) -> Option<&ImportEdit> {
+        self.import_edit.as_ref()
+    }
+
+    pub fn cursor_offset(&self) -> Option<TextSize> {
+        self.cursor_offset
+    }
+
     pub fn text_edit(&self) -> &TextEdit {
         &self.text_edit
     }
+    pub fn set_text_edit(&mut self, text_edit: TextEdit) {
+        self.text_edit = text_edit;
+    }
 
     /// Short one-line additional information, like a type
     pub fn detail(&self) -> Option<&str> {
         self.detail.as_deref()
     }
+    pub fn set_detail(&mut self, detail: Option<String>) {
+        self.detail = detail;
+    }
     /// A doc-comment
     pub fn documentation(&self) -> Option<Documentation> {
         self.documentation.clone()
     }
+    /// Whether `documentation` is only a summary, see
+    /// `CompletionConfig::full_documentation`.
+    pub fn has_more_documentation(&self) -> bool {
+        self.has_more_documentation
+    }
     /// What string is used for filtering.
     pub fn lookup(&self) -> &str {
         self.lookup.as_deref().unwrap_or(&self.label)
@@ -235,9 +366,82 @@ impl CompletionItem {
         self.score
     }
 
+    pub fn deprioritized(&self) -> bool {
+        self.deprioritized
+    }
+
+    pub fn referenced_nearby(&self) -> bool {
+        self.referenced_nearby
+    }
+
+    pub fn self_member(&self) -> bool {
+        self.self_member
+    }
+
+    pub fn word_match(&self) -> bool {
+        self.word_match
+    }
+
+    pub fn module_distance(&self) -> Option<u32> {
+        self.module_distance
+    }
+
+    /// The text clients should sort this item by, or `None` to sort by label.
+    pub fn sort_text(&self, config: &CompletionConfig) -> Option<String> {
+        let tier = if self.score.is_some() {
+            // Sorts before everything else, even punctuation.
+            Some(' ')
+        } else if self.referenced_nearby {
+            // Sorts after the space above, but before letters.
+            Some('!')
+        } else if self.self_member {
+            // Sorts right after the tier above.
+            Some('"')
+        } else if self.word_match {
+            // Sorts right after the tier above.
+            Some('#')
+        } else if self.deprioritized {
+            // Sorts after letters, so this puts the item after the others.
+            Some('~')
+        } else {
+            None
+        };
+        let preferred_kind = match config.member_preference {
+            MemberPreference::None => None,
+            MemberPreference::Fields => Some(CompletionItemKind::Field),
+            MemberPreference::Methods => Some(CompletionItemKind::Method),
+        };
+        let has_tie_breakers = preferred_kind.is_some()
+            || self.module_distance.is_some()
+            || config.prefer_shorter_labels;
+        if tier.is_none() && !has_tie_breakers {
+            return None;
+        }
+        // `_` sorts between the tiers above. The padded numbers break ties
+        // between items of the same tier.
+        let mut res = tier.unwrap_or('_').to_string();
+        if let Some(kind) = preferred_kind {
+            res.push(if self.kind == Some(kind) { '0' } else { '1' });
+        }
+        format_to!(res, "{:02}", self.module_distance.unwrap_or(0).min(99));
+        if config.prefer_shorter_labels {
+            format_to!(res, "{:04}", self.label.len());
+        }
+        res.push_str(&self.label);
+        Some(res)
+    }
+
+    pub fn relevance(&self) -> Option<CompletionRelevance> {
+        self.relevance
+    }
+
     pub fn trigger_call_info(&self) -> bool {
         self.trigger_call_info
     }
+
+    pub fn trigger_completion(&self) -> bool {
+        self.trigger_completion
+    }
 }
 
 /// A helper to make `CompletionItem`s.
@@ -253,9 +457,18 @@ pub(crate) struct Builder {
     lookup: Option<String>,
     kind: Option<CompletionItemKind>,
     text_edit: Option<TextEdit>,
+    import_edit: Option<ImportEdit>,
+    cursor_offset: Option<TextSize>,
     deprecated: Option<bool>,
     trigger_call_info: Option<bool>,
+    trigger_completion: Option<bool>,
     score: Option<CompletionScore>,
+    deprioritized: Option<bool>,
+    referenced_nearby: Option<bool>,
+    self_member: Option<bool>,
+    word_match: Option<bool>,
+    module_distance: Option<u32>,
+    relevance: Option<CompletionRelevance>,
 }
 
 impl Builder {
@@ -265,27 +478,44 @@ impl Builder {
 
     pub(crate) fn build(self) -> CompletionItem {
         let label = self.label;
-        let text_edit = match self.text_edit {
+        let mut text_edit = match self.text_edit {
             Some(it) => it,
             None => TextEdit::replace(
                 self.source_range,
                 self.insert_text.unwrap_or_else(|| label.clone()),
             ),
         };
+        if let Some(import_edit) = &self.import_edit {
+            // Clients apply the edits outside of the source range as
+            // additional text edits.
+            if text_edit.union(import_edit.edit.clone()).is_err() {
+                log::warn!("import edit overlaps the completion of {:?}", label);
+            }
+        }
 
         CompletionItem {
             source_range: self.source_range,
             label,
             insert_text_format: self.insert_text_format,
             text_edit,
+            import_edit: self.import_edit,
+            cursor_offset: self.cursor_offset,
             detail: self.detail,
             documentation: self.documentation,
+            has_more_documentation: false,
             lookup: self.lookup,
             kind: self.kind,
             completion_kind: self.completion_kind,
             deprecated: self.deprecated.unwrap_or(false),
             trigger_call_info: self.trigger_call_info.unwrap_or(false),
+            trigger_completion: self.trigger_completion.unwrap_or(false),
             score: self.score,
+            deprioritized: self.deprioritized.unwrap_or(false),
+            referenced_nearby: self.referenced_nearby.unwrap_or(false),
+            self_member: self.self_member.unwrap_or(false),
+            word_match: self.word_match.unwrap_or(false),
+            module_distance: self.module_distance,
+            relevance: self.relevance,
         }
     }
     pub(crate) fn lookup_by(mut self, lookup: impl Into<String>) -> Builder {
@@ -308,6 +538,13 @@ impl Builder {
         self.insert_text_format = InsertTextFormat::Snippet;
         self.insert_text(snippet)
     }
+    /// Inserts `snippet` as plain text, for clients without snippet support,
+    /// and remembers where its `$0` was.
+    pub(crate) fn insert_snippet_as_text(mut self, snippet: &str) -> Builder {
+        let (text, cursor_offset) = snippet_to_plain_text(snippet);
+        self.cursor_offset = Some(cursor_offset);
+        self.insert_text(text)
+    }
     pub(crate) fn kind(mut self, kind: CompletionItemKind) -> Builder {
         self.kind = Some(kind);
         self
@@ -316,6 +553,12 @@ impl Builder {
         self.text_edit = Some(edit);
         self
     }
+    /// Adds a `use` item along with the completion, which is needed to
+    /// bring the completed item into scope.
+    pub(crate) fn import_edit(mut self, import_edit: ImportEdit) -> Builder {
+        self.import_edit = Some(import_edit);
+        self
+    }
     pub(crate) fn snippet_edit(mut self, _cap: SnippetCap, edit: TextEdit) -> Builder {
         self.insert_text_format = InsertTextFormat::Snippet;
         self.text_edit(edit)
@@ -344,10 +587,38 @@ impl Builder {
         self.score = Some(score);
         self
     }
+    pub(crate) fn set_deprioritized(mut self, deprioritized: bool) -> Builder {
+        self.deprioritized = Some(deprioritized);
+        self
+    }
+    pub(crate) fn set_module_distance(mut self, module_distance: Option<u32>) -> Builder {
+        self.module_distance = module_distance;
+        self
+    }
+    pub(crate) fn set_referenced_nearby(mut self, referenced_nearby: bool) -> Builder {
+        self.referenced_nearby = Some(referenced_nearby);
+        self
+    }
+    pub(crate) fn set_self_member(mut self, self_member: bool) -> Builder {
+        self.self_member = Some(self_member);
+        self
+    }
+    pub(crate) fn set_word_match(mut self, word_match: bool) -> Builder {
+        self.word_match = Some(word_match);
+        self
+    }
+    pub(crate) fn set_relevance(mut self, relevance: Option<CompletionRelevance>) -> Builder {
+        self.relevance = relevance;
+        self
+    }
     pub(crate) fn trigger_call_info(mut self) -> Builder {
         self.trigger_call_info = Some(true);
         self
     }
+    pub(crate) fn trigger_completion(mut self) -> Builder {
+        self.trigger_completion = Some(true);
+        self
+    }
 }
 
 impl<'a> Into<CompletionItem> for Builder {
@@ -357,14 +628,75 @@ impl<'a> Into<CompletionItem> for Builder {
 }
 
 /// Represents an in-progress set of completions being built.
-#[derive(Debug, Default)]
-pub(crate) struct Completions {
+///
+/// By default, items are buffered until the completion is done. A streaming
+/// `Completions` instead hands each item to its sink as soon as it is added;
+/// once the sink returns `false`, further items are dropped.
+#[derive(Default)]
+pub(crate) struct Completions<'a> {
     buf: Vec<CompletionItem>,
+    sink: Option<&'a mut dyn FnMut(CompletionItem) -> bool>,
+    hook: Option<CompletionItemHook>,
+    min_score: Option<CompletionScore>,
+    summarize_docs: bool,
+    plain_text_docs: bool,
+    is_done: bool,
 }
 
-impl Completions {
+impl<'a> Completions<'a> {
+    pub(crate) fn streaming(sink: &'a mut dyn FnMut(CompletionItem) -> bool) -> Completions<'a> {
+        Completions { sink: Some(sink), ..Completions::default() }
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: Option<CompletionItemHook>) {
+        self.hook = hook;
+    }
+
+    pub(crate) fn set_min_score(&mut self, min_score: Option<CompletionScore>) {
+        self.min_score = min_score;
+    }
+
+    pub(crate) fn set_summarize_docs(&mut self, summarize_docs: bool) {
+        self.summarize_docs = summarize_docs;
+    }
+
+    pub(crate) fn set_plain_text_docs(&mut self, plain_text_docs: bool) {
+        self.plain_text_docs = plain_text_docs;
+    }
+
+    /// Whether the streaming sink asked for no more items.
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+
     pub(crate) fn add(&mut self, item: impl Into<CompletionItem>) {
-        self.buf.push(item.into())
+        if self.is_done {
+            return;
+        }
+        let mut item = item.into();
+        if self.min_score.is_some() && item.score() < self.min_score {
+            return;
+        }
+        if self.summarize_docs {
+            if let Some(summary) =
+                item.documentation.as_ref().and_then(|it| doc_summary(it.as_str()))
+            {
+                item.documentation = Some(Documentation::new(&summary));
+                item.has_more_documentation = true;
+            }
+        }
+        if self.plain_text_docs {
+            item.documentation = item
+                .documentation
+                .map(|it| Documentation::new(&markdown_to_plain_text(it.as_str())));
+        }
+        if let Some(hook) = &self.hook {
+            hook.apply(&mut item);
+        }
+        match &mut self.sink {
+            Some(sink) => self.is_done = !sink(item),
+            None => self.buf.push(item),
+        }
     }
     pub(crate) fn add_all<I>(&mut self, items: I)
     where
@@ -375,8 +707,247 @@ impl Completions {
     }
 }
 
-impl Into<Vec<CompletionItem>> for Completions {
+/// Turns a snippet into the text it inserts with the default placeholders,
+/// along with the offset of its final tab stop, `$0`. Without `$0`, the cursor
+/// ends up after the inserted text.
+fn snippet_to_plain_text(snippet: &str) -> (String, TextSize) {
+    let mut text = String::new();
+    let mut cursor_offset = None;
+    let mut chars = snippet.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                // `${1:placeholder}` inserts the placeholder, `${1}` nothing.
+                let mut tab_stop = String::new();
+                while let Some(digit) = chars.peek().filter(|it| it.is_ascii_digit()) {
+                    tab_stop.push(*digit);
+                    chars.next();
+                }
+                if tab_stop == "0" {
+                    cursor_offset = Some(TextSize::of(text.as_str()));
+                }
+                if chars.next() == Some(':') {
+                    text.extend(chars.by_ref().take_while(|&it| it != '}'));
+                }
+            }
+            '$' if chars.peek().map_or(false, |it| it.is_ascii_digit()) => {
+                let mut tab_stop = String::new();
+                while let Some(digit) = chars.peek().filter(|it| it.is_ascii_digit()) {
+                    tab_stop.push(*digit);
+                    chars.next();
+                }
+                if tab_stop == "0" {
+                    cursor_offset = Some(TextSize::of(text.as_str()));
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+    let cursor_offset = cursor_offset.unwrap_or_else(|| TextSize::of(text.as_str()));
+    (text, cursor_offset)
+}
+
+/// The first paragraph of `docs`, if there is more after it.
+fn doc_summary(docs: &str) -> Option<String> {
+    let mut lines = docs.trim().lines();
+    let summary: Vec<&str> = lines.by_ref().take_while(|it| !it.trim().is_empty()).collect();
+    lines.next()?;
+    Some(summary.join("\n"))
+}
+
+/// Strips the markdown formatting from doc comments, keeping the text of
+/// links and code spans, and the contents of code blocks without rustdoc's
+/// hidden lines.
+fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut res = Vec::new();
+    // The fence of the current code block, and whether it contains Rust.
+    let mut code_block: Option<bool> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            code_block = match code_block {
+                Some(_) => None,
+                None => Some(is_rust_fence(info)),
+            };
+            continue;
+        }
+        match code_block {
+            Some(true) if trimmed == "#" || trimmed.starts_with("# ") => {}
+            Some(_) => res.push(line.to_string()),
+            None if is_link_reference_definition(trimmed) => {}
+            None => res.push(inline_markdown_to_plain_text(strip_heading(line))),
+        }
+    }
+    res.join("\n")
+}
+
+/// Rustdoc treats code blocks as Rust, unless they name another language.
+fn is_rust_fence(info: &str) -> bool {
+    info.split(&[',', ' '][..]).all(|it| {
+        matches!(
+            it.trim(),
+            "" | "rust"
+                | "ignore"
+                | "should_panic"
+                | "no_run"
+                | "compile_fail"
+                | "allow_fail"
+                | "test_harness"
+        ) || it.trim().starts_with("edition")
+    })
+}
+
+/// Like `[name]: https://example.com`.
+fn is_link_reference_definition(line: &str) -> bool {
+    line.starts_with('[') && line.contains("]:")
+}
+
+/// `## Examples` becomes `Examples`.
+fn strip_heading(line: &str) -> &str {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    match text.strip_prefix(' ') {
+        Some(text) if (1..=6).contains(&level) => text,
+        _ => line,
+    }
+}
+
+fn inline_markdown_to_plain_text(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut open_emphasis = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '`' => {
+                // A code span ends with as many backticks as it starts with,
+                // its contents are kept verbatim.
+                let ticks = rest.len() - rest.trim_start_matches('`').len();
+                let after_open = &rest[ticks..];
+                match find_closing_backticks(after_open, ticks) {
+                    Some(end) => {
+                        res.push_str(after_open[..end].trim());
+                        rest = &after_open[end + ticks..];
+                    }
+                    None => {
+                        res.push_str(&rest[..ticks]);
+                        rest = after_open;
+                    }
+                }
+            }
+            // `a[0]` is an index rather than a link.
+            '[' if matches!(res.chars().last(), Some(it) if it.is_alphanumeric() || it == ']') => {
+                res.push('[');
+                rest = &rest[1..];
+            }
+            '[' => match parse_link(rest) {
+                Some((link_text, after_link)) => {
+                    res.push_str(&inline_markdown_to_plain_text(link_text));
+                    rest = after_link;
+                }
+                None => {
+                    res.push('[');
+                    rest = &rest[1..];
+                }
+            },
+            '*' | '_' => {
+                // Emphasis delimiters touch the emphasized text on one side,
+                // and are not part of a word on the other. Unmatched ones,
+                // like in `2 * 3`, are kept.
+                let run = rest.len() - rest.trim_start_matches(c).len();
+                let delimiter = &rest[..run];
+                rest = &rest[run..];
+                let before = res.chars().last();
+                let after = rest.chars().next();
+                let is_word = |it: Option<char>| matches!(it, Some(it) if it.is_alphanumeric());
+                let is_space = |it: Option<char>| it.filter(|it| !it.is_whitespace()).is_none();
+                let closes = !is_space(before) && !is_word(after);
+                let opens = !is_space(after) && !is_word(before);
+                if closes && open_emphasis.last() == Some(&delimiter) {
+                    open_emphasis.pop();
+                } else if opens && rest.contains(delimiter) {
+                    open_emphasis.push(delimiter);
+                } else {
+                    res.push_str(delimiter);
+                }
+            }
+            _ => {
+                res.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    res
+}
+
+/// The offset of a run of exactly `ticks` backticks in `text`.
+fn find_closing_backticks(text: &str, ticks: usize) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find('`') {
+        let start = offset + start;
+        let run = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return Some(start);
+        }
+        offset = start + run;
+    }
+    None
+}
+
+/// Parses `[text](url)`, `[text][ref]` and `[text]` at the start of `text`,
+/// returning the link text and what follows the link.
+fn parse_link(text: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(idx);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let link_text = &text[1..close];
+    let rest = &text[close + 1..];
+    let destination_end = match rest.chars().next() {
+        Some('(') => rest.find(')')? + 1,
+        Some('[') => rest.find(']')? + 1,
+        _ => 0,
+    };
+    Some((link_text, &rest[destination_end..]))
+}
+
+impl Into<Vec<CompletionItem>> for Completions<'_> {
     fn into(self) -> Vec<CompletionItem> {
         self.buf
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ra_syntax::TextSize;
+
+    use super::snippet_to_plain_text;
+
+    fn check(snippet: &str, text: &str, cursor_offset: u32) {
+        assert_eq!(
+            snippet_to_plain_text(snippet),
+            (text.to_string(), TextSize::from(cursor_offset))
+        );
+    }
+
+    #[test]
+    fn snippet_tab_stops_without_placeholders() {
+        check("foo(${1}, ${0})", "foo(, )", 6);
+        check("foo(${1:x}, $2)$0", "foo(x, )", 8);
+        check("${0:unimplemented!()}", "unimplemented!()", 0);
+        check("bar", "bar", 3);
+    }
+}
@@ -0,0 +1,58 @@
+//! Matching of the typed text against the names of completion candidates.
+
+/// Whether `query` matches the start of the words of `name`, where words are
+/// separated by underscores or start at a lowercase to uppercase transition.
+/// Each word of the query has to be a prefix of a word of the name, in order,
+/// so `mF` and `myField` match `my_field`, but `fm` does not.
+pub(super) fn matches_word_starts(query: &str, name: &str) -> bool {
+    let query_words = words(query);
+    if query_words.is_empty() {
+        return false;
+    }
+    let mut name_words = words(name).into_iter();
+    query_words.iter().all(|query_word| {
+        name_words.any(|name_word| {
+            name_word.len() >= query_word.len()
+                && name_word[..query_word.len()].eq_ignore_ascii_case(query_word)
+        })
+    })
+}
+
+fn words(text: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    let mut prev_lower = false;
+    for (idx, c) in text.char_indices() {
+        if c == '_' {
+            if start < idx {
+                res.push(&text[start..idx]);
+            }
+            start = idx + 1;
+        } else if c.is_uppercase() && prev_lower {
+            res.push(&text[start..idx]);
+            start = idx;
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if start < text.len() {
+        res.push(&text[start..]);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_word_starts;
+
+    #[test]
+    fn matches_on_case_boundaries() {
+        assert!(matches_word_starts("mF", "my_field"));
+        assert!(matches_word_starts("myField", "my_field"));
+        assert!(matches_word_starts("my_f", "my_field"));
+        assert!(matches_word_starts("mF", "myField"));
+        assert!(matches_word_starts("f", "my_field"));
+        assert!(!matches_word_starts("fm", "my_field"));
+        assert!(!matches_word_starts("mx", "my_field"));
+        assert!(!matches_word_starts("", "my_field"));
+    }
+}
@@ -112,6 +112,31 @@ fn test_if_is_prev() {
     check_pattern_is_applicable(r"if l<|>", if_is_prev);
 }
 
+pub(crate) fn ref_type_amp_is_prev(element: SyntaxElement) -> bool {
+    element
+        .into_token()
+        .and_then(|it| previous_non_trivia_token(it))
+        .filter(|it| it.kind() == AMP && it.parent().kind() == REFERENCE_TYPE)
+        .is_some()
+}
+#[test]
+fn test_ref_type_amp_is_prev() {
+    check_pattern_is_applicable(r"fn foo(x: &i<|>) {}", ref_type_amp_is_prev);
+}
+
+pub(crate) fn break_or_continue_is_prev(element: SyntaxElement) -> bool {
+    element
+        .into_token()
+        .and_then(|it| previous_non_trivia_token(it))
+        .filter(|it| it.kind() == BREAK_KW || it.kind() == CONTINUE_KW)
+        .is_some()
+}
+#[test]
+fn test_break_or_continue_is_prev() {
+    check_pattern_is_applicable(r"fn f() { loop { break <|> } }", break_or_continue_is_prev);
+    check_pattern_is_applicable(r"fn f() { loop { continue '<|> } }", break_or_continue_is_prev);
+}
+
 pub(crate) fn has_trait_as_prev_sibling(element: SyntaxElement) -> bool {
     previous_sibling_or_ancestor_sibling(element).filter(|it| it.kind() == TRAIT_DEF).is_some()
 }
@@ -128,6 +153,30 @@ fn test_has_impl_as_prev_sibling() {
     check_pattern_is_applicable(r"impl A w<|> {}", has_impl_as_prev_sibling);
 }
 
+/// Labels of the loops enclosing `element`, innermost first.
+pub(crate) fn enclosing_loop_labels(element: SyntaxElement) -> Vec<String> {
+    let leaf = match element {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent(),
+    };
+    let mut labels = Vec::new();
+    for node in leaf.ancestors() {
+        if node.kind() == FN_DEF || node.kind() == LAMBDA_EXPR {
+            break;
+        }
+        let label = match_ast! {
+            match node {
+                ast::ForExpr(it) => it.label(),
+                ast::WhileExpr(it) => it.label(),
+                ast::LoopExpr(it) => it.label(),
+                _ => None,
+            }
+        };
+        labels.extend(label.and_then(|it| it.lifetime_token()).map(|it| it.text().to_string()));
+    }
+    labels
+}
+
 pub(crate) fn is_in_loop_body(element: SyntaxElement) -> bool {
     let leaf = match element {
         NodeOrToken::Node(node) => node,
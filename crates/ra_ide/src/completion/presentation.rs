@@ -1,52 +1,162 @@
 //! This modules takes care of rendering various definitions as completion items.
 
-use hir::{Docs, HasAttrs, HasSource, HirDisplay, ModPath, ScopeDef, StructKind, Type};
-use ra_syntax::ast::NameOwner;
+use std::iter;
+
+use hir::{
+    AsAssocItem, AssocItemContainer, BuiltinType, Docs, Documentation, FloatBitness, HasAttrs,
+    HasSource, HasVisibility, HirDisplay, IntBitness, ModPath, ScopeDef, Signedness, StructKind,
+    Type,
+};
+use ra_assists::utils::use_statement_edit;
+use ra_syntax::{
+    ast::{self, NameOwner, TypeAscriptionOwner, TypeParamsOwner},
+    AstNode, SyntaxKind, TextRange,
+};
+use ra_text_edit::TextEdit;
 use stdx::SepBy;
 use test_utils::mark;
 
 use crate::{
     completion::{
-        completion_item::Builder, CompletionContext, CompletionItem, CompletionItemKind,
-        CompletionKind, Completions,
+        completion_config::SnippetCap, completion_context::is_numeric_type,
+        completion_item::Builder, fuzzy::matches_word_starts, CompletionContext, CompletionItem,
+        CompletionItemKind, CompletionKind, Completions, ImportEdit,
+    },
+    display::{
+        const_label, generic_params_with_defaults, macro_label, static_label, type_label,
+        FunctionSignature,
     },
-    display::{const_label, macro_label, type_label, FunctionSignature},
-    CompletionScore, RootDatabase,
+    hover::determine_mod_path,
+    CompletionRelevance, CompletionScore, RootDatabase,
 };
 
-impl Completions {
+impl Completions<'_> {
     pub(crate) fn add_field(&mut self, ctx: &CompletionContext, field: hir::Field, ty: &Type) {
+        self.add_field_impl(ctx, field, ty, false)
+    }
+
+    /// Adds a field of a record literal which can be initialized with the
+    /// shorthand syntax, because a binding with the same name and type is in
+    /// scope.
+    pub(crate) fn add_field_shorthand(
+        &mut self,
+        ctx: &CompletionContext,
+        field: hir::Field,
+        ty: &Type,
+    ) {
+        self.add_field_impl(ctx, field, ty, true)
+    }
+
+    fn add_field_impl(
+        &mut self,
+        ctx: &CompletionContext,
+        field: hir::Field,
+        ty: &Type,
+        is_shorthand: bool,
+    ) {
         let is_deprecated = is_deprecated(field, ctx.db);
         let name = field.name(ctx.db);
+        let mut detail = ty.display(ctx.db).to_string();
+        if is_shorthand {
+            detail = with_note(Some(detail), "shorthand");
+        }
         let mut completion_item =
             CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.to_string())
                 .kind(CompletionItemKind::Field)
-                .detail(ty.display(ctx.db).to_string())
+                .detail(detail)
                 .set_documentation(field.docs(ctx.db))
-                .set_deprecated(is_deprecated);
+                .set_deprecated(is_deprecated)
+                .set_self_member(!is_shorthand && is_self_member(ctx))
+                .set_word_match(matches_typed_words(ctx, &name.to_string()));
 
-        if let Some(score) = compute_score(ctx, &ty, &name.to_string()) {
+        let score = if is_shorthand {
+            Some(CompletionScore::TypeAndNameMatch)
+        } else {
+            compute_score(ctx, &ty, &name.to_string())
+        };
+        if let Some(score) = score {
             completion_item = completion_item.set_score(score);
         }
 
-        completion_item.add_to(self);
+        completion_item
+            .set_relevance(compute_relevance(ctx, score, false, is_deprecated))
+            .add_to(self);
     }
 
-    pub(crate) fn add_tuple_field(&mut self, ctx: &CompletionContext, field: usize, ty: &Type) {
-        CompletionItem::new(CompletionKind::Reference, ctx.source_range(), field.to_string())
+    /// Adds a field of the `T` in an `Option<T>` or `Result<T, _>` receiver,
+    /// which is accessed after unwrapping it, like `unwrap().name`.
+    pub(crate) fn add_unwrapped_field(
+        &mut self,
+        ctx: &CompletionContext,
+        field: hir::Field,
+        ty: &Type,
+    ) {
+        let name = field.name(ctx.db).to_string();
+        let access = format!("unwrap().{}", name);
+        CompletionItem::new(CompletionKind::Reference, ctx.source_range(), access.clone())
             .kind(CompletionItemKind::Field)
-            .detail(ty.display(ctx.db).to_string())
+            .lookup_by(name)
+            .insert_text(access)
+            .detail(with_note(Some(ty.display(ctx.db).to_string()), "after unwrap"))
+            .set_documentation(field.docs(ctx.db))
+            .set_deprioritized(true)
             .add_to(self);
     }
 
+    pub(crate) fn add_tuple_field(&mut self, ctx: &CompletionContext, field: usize, ty: &Type) {
+        let mut builder =
+            CompletionItem::new(CompletionKind::Reference, ctx.source_range(), field.to_string())
+                .kind(CompletionItemKind::Field)
+                .detail(ty.display(ctx.db).to_string());
+        // In `pair.0.1`, `0.1` is lexed as a float, so the receiver needs
+        // parentheses, like `(pair.0).1`.
+        if let Some(receiver) = &ctx.dot_receiver {
+            if let Some(text) = tuple_index_receiver_text(receiver) {
+                mark::hit!(parenthesizes_tuple_index_receiver);
+                let receiver_range = ctx.sema.original_range(receiver.syntax()).range;
+                let range = TextRange::new(receiver_range.start(), ctx.source_range().end());
+                builder =
+                    builder.text_edit(TextEdit::replace(range, format!("({}).{}", text, field)));
+            }
+        }
+        builder.add_to(self);
+    }
+
     pub(crate) fn add_resolution(
         &mut self,
         ctx: &CompletionContext,
         local_name: String,
         resolution: &ScopeDef,
+    ) {
+        self.add_resolution_impl(ctx, local_name, resolution, None)
+    }
+
+    /// Adds an item which is not in scope by name, but is reachable through
+    /// `qualifier`. The qualified path is inserted instead of the bare name.
+    pub(crate) fn add_qualified_resolution(
+        &mut self,
+        ctx: &CompletionContext,
+        qualifier: String,
+        local_name: String,
+        resolution: &ScopeDef,
+    ) {
+        self.add_resolution_impl(ctx, local_name, resolution, Some(qualifier))
+    }
+
+    fn add_resolution_impl(
+        &mut self,
+        ctx: &CompletionContext,
+        local_name: String,
+        resolution: &ScopeDef,
+        qualifier: Option<String>,
     ) {
         use hir::ModuleDef::*;
 
+        let qualified_name = match &qualifier {
+            Some(qualifier) => format!("{}::{}", qualifier, local_name),
+            None => local_name.clone(),
+        };
+
         let completion_kind = match resolution {
             ScopeDef::ModuleDef(BuiltinType(..)) => CompletionKind::BuiltinType,
             _ => CompletionKind::Reference,
@@ -55,7 +165,7 @@ impl Completions {
         let kind = match resolution {
             ScopeDef::ModuleDef(Module(..)) => CompletionItemKind::Module,
             ScopeDef::ModuleDef(Function(func)) => {
-                return self.add_function(ctx, *func, Some(local_name));
+                return self.add_function(ctx, *func, Some(qualified_name));
             }
             ScopeDef::ModuleDef(Adt(hir::Adt::Struct(_))) => CompletionItemKind::Struct,
             // FIXME: add CompletionItemKind::Union
@@ -63,7 +173,7 @@ impl Completions {
             ScopeDef::ModuleDef(Adt(hir::Adt::Enum(_))) => CompletionItemKind::Enum,
 
             ScopeDef::ModuleDef(EnumVariant(var)) => {
-                return self.add_enum_variant(ctx, *var, Some(local_name));
+                return self.add_enum_variant(ctx, *var, Some(qualified_name));
             }
             ScopeDef::ModuleDef(Const(..)) => CompletionItemKind::Const,
             ScopeDef::ModuleDef(Static(..)) => CompletionItemKind::Static,
@@ -75,7 +185,7 @@ impl Completions {
             // (does this need its own kind?)
             ScopeDef::AdtSelfType(..) | ScopeDef::ImplSelfType(..) => CompletionItemKind::TypeParam,
             ScopeDef::MacroDef(mac) => {
-                return self.add_macro(ctx, Some(local_name), *mac);
+                return self.add_macro(ctx, Some(qualified_name), *mac);
             }
             ScopeDef::Unknown => {
                 return self.add(CompletionItem::new(
@@ -97,35 +207,181 @@ impl Completions {
             _ => None,
         };
 
-        let mut completion_item =
-            CompletionItem::new(completion_kind, ctx.source_range(), local_name.clone());
+        let unstable = scope_def_attrs(ctx.db, resolution).and_then(|it| unstable_note(&it));
+        if unstable.is_some() && ctx.config.hide_unstable {
+            mark::hit!(hides_unstable_items);
+            return;
+        }
+        if let ScopeDef::ModuleDef(def) = resolution {
+            let module = def.module(ctx.db);
+            let attrs = scope_def_attrs(ctx.db, resolution);
+            if attrs.map_or(false, |it| is_hidden_from_docs(ctx, &it, module)) {
+                mark::hit!(hides_doc_hidden_items);
+                return;
+            }
+        }
+
+        let mut detail = None;
         if let ScopeDef::Local(local) = resolution {
             let ty = local.ty(ctx.db);
             if !ty.is_unknown() {
-                completion_item = completion_item.detail(ty.display(ctx.db).to_string());
+                detail = Some(ty.display(ctx.db).to_string());
             }
+            self.add_wrapped_value(ctx, &local_name, &ty);
+            self.add_converted_value(ctx, &local_name, &ty);
         };
 
-        if let ScopeDef::Local(local) = resolution {
-            if let Some(score) = compute_score(ctx, &local.ty(ctx.db), &local_name) {
-                completion_item = completion_item.set_score(score);
+        // Show `static mut`, as accessing those is unsafe.
+        if let ScopeDef::ModuleDef(Static(it)) = resolution {
+            detail = Some(static_label(&it.source(ctx.db).value));
+        }
+
+        if let ScopeDef::ModuleDef(Adt(adt)) = resolution {
+            if let Some(params) = adt_generic_params_with_defaults(ctx.db, *adt) {
+                detail = Some(format!("{}{}", adt.name(ctx.db), params));
+            }
+        }
+
+        if let ScopeDef::ModuleDef(Trait(it)) = resolution {
+            let super_traits = it.direct_super_traits(ctx.db);
+            if !super_traits.is_empty() {
+                let bounds = super_traits.iter().map(|it| it.name(ctx.db)).sep_by(" + ");
+                detail = Some(format!("trait {}: {}", it.name(ctx.db), bounds));
+            }
+        }
+
+        if let ScopeDef::ModuleDef(Adt(hir::Adt::Enum(it))) = resolution {
+            if ctx.config.show_enum_variant_count {
+                let count = it.variants(ctx.db).len();
+                let note = format!("{} variant{}", count, if count == 1 { "" } else { "s" });
+                detail = Some(with_note(detail, &note));
+            }
+        }
+
+        if let ScopeDef::ModuleDef(Adt(adt)) = resolution {
+            if ctx.config.show_type_layout {
+                if let Some(layout) = type_layout(ctx.db, &adt.ty(ctx.db), 0) {
+                    let note = format!("size {}, align {}", layout.size, layout.align);
+                    detail = Some(with_note(detail, &note));
+                }
             }
         }
 
-        // Add `<>` for generic types
-        if ctx.is_path_type && !ctx.has_type_args && ctx.config.add_call_parenthesis {
+        if let Some(unstable) = unstable {
+            detail = Some(with_note(detail, &unstable));
+        }
+        if let Some(attrs) = scope_def_attrs(ctx.db, resolution) {
+            for note in deprecation_notes(&attrs) {
+                detail = Some(with_note(detail, &note));
+            }
+        }
+
+        if let ScopeDef::ModuleDef(def) = resolution {
+            if let Some(visibility) = visibility_note(ctx, *def) {
+                detail = Some(with_note(detail, &visibility));
+            }
+            if let Some(origin) = def.module(ctx.db).and_then(|it| item_origin(ctx, it)) {
+                detail = Some(with_note(detail, &origin));
+            }
+        }
+
+        if qualifier.is_some() {
+            detail = Some(with_note(detail, &qualified_name));
+        }
+
+        let mut completion_item =
+            CompletionItem::new(completion_kind, ctx.source_range(), qualified_name.clone())
+                .set_detail(detail);
+        if qualifier.is_some() {
+            completion_item = completion_item.lookup_by(local_name.clone());
+        }
+        let is_type = matches!(
+            resolution,
+            ScopeDef::ModuleDef(Adt(_))
+                | ScopeDef::ModuleDef(TypeAlias(_))
+                | ScopeDef::ModuleDef(BuiltinType(_))
+                | ScopeDef::GenericParam(_)
+                | ScopeDef::AdtSelfType(_)
+                | ScopeDef::ImplSelfType(_)
+        );
+        if let ScopeDef::ModuleDef(def) = resolution {
+            // Traits are implemented with `impl Trait for S`, so they're
+            // unlikely before `for` is typed.
+            let is_unlikely_trait =
+                ctx.is_inherent_impl_target && matches!(def, hir::ModuleDef::Trait(_));
+            if is_unlikely_trait {
+                mark::hit!(deprioritizes_traits_in_inherent_impls);
+            }
+            // Only types, or the modules containing them, make sense as the
+            // type of a field.
+            let is_unlikely_field_type =
+                ctx.is_field_def_type && !is_type && !matches!(def, hir::ModuleDef::Module(_));
+            if is_unlikely_field_type {
+                mark::hit!(deprioritizes_non_types_in_field_defs);
+            }
+            let module_distance = match (def.module(ctx.db), ctx.scope().module()) {
+                (Some(def_module), Some(module)) => {
+                    Some(module_distance(ctx.db, module, def_module)).filter(|&it| it > 0)
+                }
+                _ => None,
+            };
+            completion_item = completion_item
+                .set_module_distance(module_distance)
+                .set_deprioritized(
+                    is_prelude_item(ctx, *def) || is_unlikely_trait || is_unlikely_field_type,
+                )
+                .set_referenced_nearby(ctx.referenced_names.contains(&local_name));
+        }
+
+        let score = match resolution {
+            ScopeDef::Local(local) => compute_score(ctx, &local.ty(ctx.db), &local_name),
+            ScopeDef::ModuleDef(Adt(adt)) => compute_ctor_score(ctx, &adt.ty(ctx.db)),
+            ScopeDef::ModuleDef(Const(it)) => {
+                const_arg_score(ctx, *it).or_else(|| range_bound_score(ctx, *it))
+            }
+            _ => None,
+        };
+        if let Some(score) = score {
+            completion_item = completion_item.set_score(score);
+        }
+        let is_local = matches!(resolution, ScopeDef::Local(_));
+        let is_deprecated = scope_def_attrs(ctx.db, resolution)
+            .map_or(false, |it| it.by_key("deprecated").exists());
+        completion_item =
+            completion_item.set_relevance(compute_relevance(ctx, score, is_local, is_deprecated));
+
+        // Add `<>` for generic types, but not in macro arguments, where the
+        // macro may expect a bare path.
+        if ctx.is_path_type && ctx.is_in_macro_args {
+            mark::hit!(hides_generics_in_macro_args);
+        }
+        if ctx.is_path_type
+            && !ctx.has_type_args
+            && !ctx.is_in_macro_args
+            && ctx.config.add_call_parenthesis
+        {
             if let Some(cap) = ctx.config.snippet_cap {
-                let has_non_default_type_params = match resolution {
-                    ScopeDef::ModuleDef(Adt(it)) => it.has_non_default_type_params(ctx.db),
-                    ScopeDef::ModuleDef(TypeAlias(it)) => it.has_non_default_type_params(ctx.db),
-                    _ => false,
+                let type_params = match resolution {
+                    ScopeDef::ModuleDef(Adt(it)) => {
+                        non_default_type_param_names(ctx.db, (*it).into())
+                    }
+                    ScopeDef::ModuleDef(TypeAlias(it)) => {
+                        non_default_type_param_names(ctx.db, (*it).into())
+                    }
+                    _ => Vec::new(),
                 };
-                if has_non_default_type_params {
+                if !type_params.is_empty() {
                     mark::hit!(inserts_angle_brackets_for_generics);
+                    let placeholders = type_params
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, name)| format!("${{{}:{}}}", idx + 1, name))
+                        .sep_by(", ")
+                        .to_string();
                     completion_item = completion_item
                         .lookup_by(local_name.clone())
-                        .label(format!("{}<…>", local_name))
-                        .insert_snippet(cap, format!("{}<$0>", local_name));
+                        .label(format!("{}<…>", qualified_name))
+                        .insert_snippet(cap, format!("{}<{}>$0", qualified_name, placeholders));
                 }
             }
         }
@@ -133,6 +389,81 @@ impl Completions {
         completion_item.kind(kind).set_documentation(docs).add_to(self)
     }
 
+    /// Offers `Some(x)` for a local `x: T` where an `Option<T>` is returned,
+    /// and `Ok(x)` where a `Result<T, _>` is.
+    fn add_wrapped_value(&mut self, ctx: &CompletionContext, name: &str, ty: &Type) {
+        let (wrapper, inner_ty) = match &ctx.expected_wrapper {
+            Some(it) => it,
+            None => return,
+        };
+        // An unknown type unifies with anything, which would offer every local.
+        if ty.is_unknown() || inner_ty.is_unknown() || !ty.could_unify_with(inner_ty) {
+            return;
+        }
+        let wrapped = format!("{}({})", wrapper, name);
+        CompletionItem::new(CompletionKind::Reference, ctx.source_range(), wrapped.clone())
+            .kind(CompletionItemKind::Binding)
+            .detail(format!("{}: {}", name, ty.display(ctx.db)))
+            .insert_text(wrapped)
+            .set_score(CompletionScore::TypeMatch)
+            .add_to(self);
+    }
+
+    /// Offers `x.into()` for a local `x` of another numeric type than the
+    /// expected one if there is a `From` impl to convert it, and `x as T`
+    /// otherwise, but only if the cast is lossless.
+    fn add_converted_value(&mut self, ctx: &CompletionContext, name: &str, ty: &Type) {
+        let expected = match &ctx.expected_type {
+            Some(it) => it,
+            None => return,
+        };
+        let (from, to) = match (ty.as_builtin(), expected.as_builtin()) {
+            (Some(from), Some(to)) if from != to => (from, to),
+            _ => return,
+        };
+        if !is_numeric_type(ty) || !is_numeric_type(expected) {
+            return;
+        }
+        let has_from_impl = ctx
+            .from_trait
+            .map_or(false, |it| expected.impls_trait(ctx.db, it, std::slice::from_ref(ty)));
+        let converted = if has_from_impl {
+            format!("{}.into()", name)
+        } else if is_widening_cast(from, to) {
+            format!("{} as {}", name, expected.display(ctx.db))
+        } else {
+            mark::hit!(skips_lossy_numeric_casts);
+            return;
+        };
+        CompletionItem::new(CompletionKind::Reference, ctx.source_range(), converted.clone())
+            .kind(CompletionItemKind::Binding)
+            .detail(format!("{}: {}", name, ty.display(ctx.db)))
+            .insert_text(converted)
+            .add_to(self);
+    }
+
+    /// Adds a snippet which is not backed by a definition, like `pd` for a
+    /// debug print. The `lookup` is what is typed to pick it, if that isn't
+    /// the label, like `tfn` for "Test function".
+    pub(crate) fn add_snippet(
+        &mut self,
+        ctx: &CompletionContext,
+        cap: SnippetCap,
+        label: &str,
+        lookup: Option<&str>,
+        snippet: &str,
+        docs: Option<&str>,
+    ) {
+        let mut builder = CompletionItem::new(CompletionKind::Snippet, ctx.source_range(), label)
+            .insert_snippet(cap, snippet)
+            .kind(CompletionItemKind::Snippet)
+            .set_documentation(docs.map(Documentation::new));
+        if let Some(lookup) = lookup {
+            builder = builder.lookup_by(lookup);
+        }
+        builder.add_to(self);
+    }
+
     pub(crate) fn add_macro(
         &mut self,
         ctx: &CompletionContext,
@@ -151,7 +482,10 @@ impl Completions {
         };
 
         let ast_node = macro_.source(ctx.db).value;
-        let detail = macro_label(&ast_node);
+        let mut detail = macro_label(&ast_node);
+        if let Some(origin) = macro_.module(ctx.db).and_then(|it| item_origin(ctx, it)) {
+            detail = with_note(Some(detail), &origin);
+        }
 
         let docs = macro_.docs(ctx.db);
 
@@ -167,14 +501,14 @@ impl Completions {
 
         let needs_bang = ctx.use_item_syntax.is_none() && !ctx.is_macro_call;
         builder = match ctx.config.snippet_cap {
-            Some(cap) if needs_bang => {
+            Some(cap) if needs_bang && ctx.config.add_macro_braces => {
                 let docs = docs.as_ref().map_or("", |s| s.as_str());
                 let (bra, ket) = guess_macro_braces(&name, docs);
                 builder
                     .insert_snippet(cap, format!("{}!{}$0{}", name, bra, ket))
                     .label(format!("{}!{}…{}", name, bra, ket))
             }
-            None if needs_bang => builder.insert_text(format!("{}!", name)),
+            _ if needs_bang => builder.insert_text(format!("{}!", name)),
             _ => {
                 mark::hit!(dont_insert_macro_call_parens_unncessary);
                 builder.insert_text(name)
@@ -190,33 +524,240 @@ impl Completions {
         func: hir::Function,
         local_name: Option<String>,
     ) {
+        self.add_function_impl(ctx, func, local_name, None, false, None)
+    }
+
+    /// Adds a method which is reachable only through `Deref`, like a method of
+    /// `T` called on a `Box<T>`.
+    pub(crate) fn add_deref_method(
+        &mut self,
+        ctx: &CompletionContext,
+        func: hir::Function,
+        deref_chain: String,
+    ) {
+        self.add_function_impl(ctx, func, None, Some(format!("via {}", deref_chain)), false, None)
+    }
+
+    /// Adds an associated function of a trait completed through the trait
+    /// path, like `Trait::<|>`. Methods are called UFCS-style there, so the
+    /// receiver becomes the first argument.
+    pub(crate) fn add_trait_function(&mut self, ctx: &CompletionContext, func: hir::Function) {
+        // With `<S as Trait>::`, the impl for `S` is called, so whether the
+        // trait provides a default body doesn't matter.
+        let (note, self_sig) = if ctx.is_qualified_self_path {
+            let self_sig = ctx.qualified_self_type.as_ref().and_then(|self_ty| {
+                let impl_def = trait_impl_for(ctx, func, self_ty);
+                func.signature_for_self_ty(ctx.db, self_ty, impl_def)
+            });
+            (None, self_sig)
+        } else {
+            let has_default = func.source(ctx.db).value.body().is_some();
+            (Some(if has_default { "default" } else { "required" }.to_string()), None)
+        };
+        self.add_function_impl(ctx, func, None, note, true, self_sig)
+    }
+
+    /// Adds a method called as an associated function through its type, like
+    /// `Vec::push`, so the receiver becomes the first argument.
+    pub(crate) fn add_ufcs_method(
+        &mut self,
+        ctx: &CompletionContext,
+        func: hir::Function,
+        path: String,
+    ) {
+        self.add_function_impl(ctx, func, Some(path), None, true, None)
+    }
+
+    fn add_function_impl(
+        &mut self,
+        ctx: &CompletionContext,
+        func: hir::Function,
+        local_name: Option<String>,
+        note: Option<String>,
+        is_ufcs: bool,
+        self_sig: Option<(Vec<Type>, Type)>,
+    ) {
+        let attrs = func.attrs(ctx.db);
+        let unstable = unstable_note(&attrs);
+        if unstable.is_some() && ctx.config.hide_unstable {
+            mark::hit!(hides_unstable_items);
+            return;
+        }
+        if is_hidden_from_docs(ctx, &attrs, Some(func.module(ctx.db))) {
+            mark::hit!(hides_doc_hidden_items);
+            return;
+        }
+
         let has_self_param = func.has_self_param(ctx.db);
 
         let name = local_name.unwrap_or_else(|| func.name(ctx.db).to_string());
         let ast_node = func.source(ctx.db).value;
-        let function_signature = FunctionSignature::from(&ast_node);
+        let mut function_signature = FunctionSignature::from(&ast_node);
+        if let Some(abi) = extern_block_abi(&ast_node) {
+            // Like rustdoc, show foreign functions as `unsafe extern "C" fn`,
+            // as they are called like one.
+            function_signature.qualifier.is_unsafe = true;
+            function_signature.qualifier.extern_abi = Some(abi);
+        }
+        if let Some((param_tys, ret_ty)) = self_sig {
+            render_self_sig(ctx, &ast_node, &mut function_signature, &param_tys, &ret_ty);
+        }
+        let mut detail = function_signature.to_string();
+        if let Some(unstable) = &unstable {
+            detail = with_note(Some(detail), unstable);
+        }
+        for note in deprecation_notes(&attrs) {
+            detail = with_note(Some(detail), &note);
+        }
+        if let Some(note) = &note {
+            detail = with_note(Some(detail), note);
+        }
+        if ctx.config.show_self_kind {
+            if let Some(self_param) = ast_node.param_list().and_then(|it| it.self_param()) {
+                detail = with_note(Some(detail), self_kind_note(&self_param));
+            }
+        }
+        if ctx.config.show_const_eval
+            && function_signature.qualifier.is_const
+            && ctx.is_in_const_context()
+        {
+            mark::hit!(notes_const_evaluable_calls);
+            detail = with_note(Some(detail), "const-evaluable");
+        }
+        if let Some(visibility) = visibility_note(ctx, func.into()) {
+            detail = with_note(Some(detail), &visibility);
+        }
+        if let Some(origin) = item_origin(ctx, func.module(ctx.db)) {
+            detail = with_note(Some(detail), &origin);
+        }
+
+        let kind =
+            if has_self_param { CompletionItemKind::Method } else { CompletionItemKind::Function };
+        let score = if ctx.dot_receiver.is_some() {
+            compute_score(ctx, &func.ret_type(ctx.db), &name)
+        } else if is_ufcs {
+            ufcs_score(ctx, func)
+        } else {
+            None
+        };
+
+        // `&mut self` methods can't be called on an immutable binding.
+        let needs_mut_receiver = !is_ufcs
+            && ctx.is_immutable_receiver()
+            && ast_node
+                .param_list()
+                .and_then(|it| it.self_param())
+                .map_or(false, |it| self_param_kind(&it) == ast::SelfParamKind::MutRef);
+        if needs_mut_receiver {
+            mark::hit!(deprioritizes_mut_methods_of_immutable_receivers);
+        }
+
+        let mut docs = func.docs(ctx.db);
+        if ctx.config.show_method_chains && has_self_param {
+            let follow_ups = follow_up_methods(ctx, &func.ret_type(ctx.db));
+            if !follow_ups.is_empty() {
+                mark::hit!(shows_follow_up_methods);
+                let hint = format!(
+                    "Follow-up methods: {}",
+                    follow_ups.iter().map(|it| format!("`{}()`", it)).sep_by(", ")
+                );
+                let text = match &docs {
+                    Some(docs) => format!("{}\n\n{}", docs.as_str(), hint),
+                    None => hint,
+                };
+                docs = Some(Documentation::new(&text));
+            }
+        }
 
         let mut builder =
             CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.clone())
-                .kind(if has_self_param {
-                    CompletionItemKind::Method
-                } else {
-                    CompletionItemKind::Function
-                })
-                .set_documentation(func.docs(ctx.db))
+                .kind(kind)
+                .set_documentation(docs)
                 .set_deprecated(is_deprecated(func, ctx.db))
-                .detail(function_signature.to_string());
+                .set_deprioritized(
+                    is_prelude_item(ctx, func.into())
+                        || needs_mut_receiver
+                        || ctx.is_field_def_type,
+                )
+                .set_referenced_nearby(ctx.referenced_names.contains(&name))
+                .set_self_member(is_self_member(ctx))
+                .set_relevance(compute_relevance(ctx, None, false, is_deprecated(func, ctx.db)))
+                .detail(detail.clone());
+        if let Some(edit) = trait_import_edit(ctx, func) {
+            builder = builder.import_edit(edit);
+        }
 
-        let params = function_signature
+        let mut params: Vec<String> = function_signature
             .parameter_names
             .iter()
             .skip(if function_signature.has_self_param { 1 } else { 0 })
             .map(|name| name.trim_start_matches('_').into())
             .collect();
+        if is_ufcs && function_signature.has_self_param {
+            mark::hit!(inserts_receiver_for_ufcs_calls);
+            params.insert(0, "self".to_string());
+        }
+
+        let type_args =
+            if ctx.config.add_call_turbofish { uninferable_type_param_count(&ast_node) } else { 0 };
+        let generics = if has_type_or_const_params(&ast_node) { "<…>" } else { "" };
+
+        let is_async = function_signature.qualifier.is_async;
+        let is_fallible = ctx.returns_result_or_option(func);
+        let suffix = match (is_async, is_fallible) {
+            (true, true) if ctx.is_in_async_fn() && ctx.is_in_fallible_fn() => Some(".await?"),
+            (true, _) if ctx.is_in_async_fn() => Some(".await"),
+            (false, true) if ctx.is_in_fallible_fn() => Some("?"),
+            _ => None,
+        };
+        if let Some(suffix) = suffix {
+            if ctx.config.snippet_cap.is_some()
+                && ctx.config.call_parens(kind).add_parens
+                && ctx.use_item_syntax.is_none()
+                && !ctx.is_call
+            {
+                mark::hit!(suggests_await_and_try_for_calls);
+                let params = Params::Named(params.clone());
+                let label = format!("{}{}", name, suffix);
+                CompletionItem::new(CompletionKind::Reference, ctx.source_range(), label.clone())
+                    .kind(kind)
+                    .set_documentation(func.docs(ctx.db))
+                    .set_deprecated(is_deprecated(func, ctx.db))
+                    .detail(detail)
+                    .add_call_parens_with_suffix(
+                        ctx,
+                        kind,
+                        name.clone(),
+                        generics,
+                        type_args,
+                        params,
+                        suffix,
+                    )
+                    .lookup_by(label)
+                    .add_to(self);
+            }
+        }
 
-        builder = builder.add_call_parens(ctx, name, Params::Named(params));
+        if let Some(score) = score {
+            builder = builder.set_score(score);
+        }
+        builder = builder.add_call_parens_with_suffix(
+            ctx,
+            kind,
+            name.clone(),
+            generics,
+            type_args,
+            Params::Named(params),
+            "",
+        );
 
-        self.add(builder)
+        if is_async && ctx.config.show_async_in_labels {
+            let mut item = builder.lookup_by(name).build();
+            item.set_label(format!("{} async", item.label()));
+            self.add(item)
+        } else {
+            self.add(builder)
+        }
     }
 
     pub(crate) fn add_const(&mut self, ctx: &CompletionContext, constant: hir::Const) {
@@ -225,14 +766,29 @@ impl Completions {
             Some(name) => name,
             _ => return,
         };
-        let detail = const_label(&ast_node);
+        let mut detail = const_label(&ast_node);
+        if let Some(visibility) = visibility_note(ctx, constant.into()) {
+            detail = with_note(Some(detail), &visibility);
+        }
+        if let Some(origin) = item_origin(ctx, constant.module(ctx.db)) {
+            detail = with_note(Some(detail), &origin);
+        }
 
-        CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.text().to_string())
-            .kind(CompletionItemKind::Const)
-            .set_documentation(constant.docs(ctx.db))
-            .set_deprecated(is_deprecated(constant, ctx.db))
-            .detail(detail)
-            .add_to(self);
+        let mut builder = CompletionItem::new(
+            CompletionKind::Reference,
+            ctx.source_range(),
+            name.text().to_string(),
+        )
+        .kind(CompletionItemKind::Const)
+        .set_documentation(constant.docs(ctx.db))
+        .set_deprecated(is_deprecated(constant, ctx.db))
+        .detail(detail);
+        if let Some(score) =
+            const_arg_score(ctx, constant).or_else(|| range_bound_score(ctx, constant))
+        {
+            builder = builder.set_score(score);
+        }
+        builder.add_to(self);
     }
 
     pub(crate) fn add_type_alias(&mut self, ctx: &CompletionContext, type_alias: hir::TypeAlias) {
@@ -241,7 +797,16 @@ impl Completions {
             Some(name) => name,
             _ => return,
         };
-        let detail = type_label(&type_def);
+        let mut detail = type_label(&type_def);
+        if let Some(trait_name) = providing_trait_name(ctx.db, type_alias) {
+            detail = with_note(Some(detail), &format!("from trait {}", trait_name));
+        }
+        if let Some(visibility) = visibility_note(ctx, type_alias.into()) {
+            detail = with_note(Some(detail), &visibility);
+        }
+        if let Some(origin) = item_origin(ctx, type_alias.module(ctx.db)) {
+            detail = with_note(Some(detail), &origin);
+        }
 
         CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.text().to_string())
             .kind(CompletionItemKind::TypeAlias)
@@ -299,6 +864,11 @@ impl Completions {
                 .surround_with("{ ", " }")
                 .to_string(),
         };
+        // Show explicit discriminants, like `A = 1`.
+        let detail = match variant.source(ctx.db).value.expr() {
+            Some(discriminant) => format!("{} = {}", detail, discriminant),
+            None => detail,
+        };
         let mut res = CompletionItem::new(
             CompletionKind::Reference,
             ctx.source_range(),
@@ -307,15 +877,21 @@ impl Completions {
         .kind(CompletionItemKind::EnumVariant)
         .set_documentation(variant.docs(ctx.db))
         .set_deprecated(is_deprecated)
+        .set_deprioritized(is_prelude_item(ctx, variant.into()))
         .detail(detail);
 
         if path.is_some() {
             res = res.lookup_by(name);
         }
 
+        let enum_ty = hir::Adt::from(variant.parent_enum(ctx.db)).ty(ctx.db);
+        if let Some(score) = compute_ctor_score(ctx, &enum_ty) {
+            res = res.set_score(score);
+        }
+
         if variant_kind == StructKind::Tuple {
             let params = Params::Anonymous(variant.fields(ctx.db).len());
-            res = res.add_call_parens(ctx, qualified_name, params)
+            res = res.add_call_parens(ctx, CompletionItemKind::EnumVariant, qualified_name, params)
         }
 
         res.add_to(self);
@@ -327,6 +903,7 @@ pub(crate) fn compute_score(
     ty: &Type,
     name: &str,
 ) -> Option<CompletionScore> {
+    let original_ty = ty;
     // FIXME: this should not fall back to string equality.
     let ty = &ty.display(ctx.db).to_string();
     let (active_name, active_type) = if let Some(record_field) = &ctx.record_field_syntax {
@@ -336,9 +913,23 @@ pub(crate) fn compute_score(
             struct_field.name(ctx.db).to_string(),
             struct_field.signature_ty(ctx.db).display(ctx.db).to_string(),
         )
+    } else if let Some(err_ty) = &ctx.expected_err_type {
+        mark::hit!(boosts_err_conversions);
+        let converts = &err_ty.display(ctx.db).to_string() == ty
+            || ctx
+                .from_trait
+                .map_or(false, |it| err_ty.impls_trait(ctx.db, it, &[original_ty.clone()]));
+        return if converts { Some(CompletionScore::TypeMatch) } else { None };
+    } else if let Some(ret_ty) = &ctx.closure_ret_type {
+        mark::hit!(boosts_closure_return_type);
+        let matches = !original_ty.is_unknown() && original_ty.could_unify_with(ret_ty);
+        return if matches { Some(CompletionScore::TypeMatch) } else { None };
     } else if let Some(active_parameter) = &ctx.active_parameter {
         mark::hit!(test_struct_field_completion_in_func_call);
         (active_parameter.name.clone(), active_parameter.ty.clone())
+    } else if let Some(dot_expected_type) = &ctx.dot_expected_type {
+        mark::hit!(boosts_methods_matching_outer_type);
+        (String::new(), dot_expected_type.display(ctx.db).to_string())
     } else {
         return None;
     };
@@ -359,6 +950,140 @@ pub(crate) fn compute_score(
     Some(res)
 }
 
+/// Explains the ranking of an item, if `CompletionConfig::explain_relevance` is set.
+fn compute_relevance(
+    ctx: &CompletionContext,
+    score: Option<CompletionScore>,
+    is_local: bool,
+    is_deprecated: bool,
+) -> Option<CompletionRelevance> {
+    if !ctx.config.explain_relevance {
+        return None;
+    }
+    Some(CompletionRelevance { is_local, is_deprecated, ..CompletionRelevance::new(score) })
+}
+
+/// Whether every value of `from` can be represented by `to`, so that `as`
+/// can't truncate or round it.
+fn is_widening_cast(from: BuiltinType, to: BuiltinType) -> bool {
+    match (from, to) {
+        (BuiltinType::Int(from), BuiltinType::Int(to)) => {
+            let (_, from_max) = int_bits(from.bitness);
+            let (to_min, _) = int_bits(to.bitness);
+            match (from.signedness, to.signedness) {
+                (Signedness::Unsigned, Signedness::Signed) => from_max < to_min,
+                (Signedness::Signed, Signedness::Unsigned) => false,
+                _ => from_max <= to_min,
+            }
+        }
+        // The mantissas of `f32` and `f64` have 24 and 53 bits.
+        (BuiltinType::Int(from), BuiltinType::Float(to)) => {
+            let (_, from_max) = int_bits(from.bitness);
+            match to.bitness {
+                FloatBitness::X32 => from_max <= 16,
+                FloatBitness::X64 => from_max <= 32,
+            }
+        }
+        (BuiltinType::Float(from), BuiltinType::Float(to)) => {
+            from.bitness == FloatBitness::X32 && to.bitness == FloatBitness::X64
+        }
+        _ => false,
+    }
+}
+
+/// The minimal and maximal width of an integer, as `usize` and `isize` have
+/// at least 16 and at most 64 bits depending on the target.
+fn int_bits(bitness: IntBitness) -> (u32, u32) {
+    match bitness {
+        IntBitness::Xsize => (16, 64),
+        IntBitness::X8 => (8, 8),
+        IntBitness::X16 => (16, 16),
+        IntBitness::X32 => (32, 32),
+        IntBitness::X64 => (64, 64),
+        IntBitness::X128 => (128, 128),
+    }
+}
+
+/// Fields and methods of `self` are what is most likely wanted inside a
+/// method, rather than the postfix completions offered after any dot.
+fn is_self_member(ctx: &CompletionContext) -> bool {
+    if !ctx.is_self_receiver() {
+        return false;
+    }
+    mark::hit!(boosts_self_members);
+    true
+}
+
+/// Methods called as associated functions are boosted if they return the
+/// expected type, like `Vec::pop` in `let n: u32 = p<|>`.
+fn ufcs_score(ctx: &CompletionContext, func: hir::Function) -> Option<CompletionScore> {
+    let expected_ty = ctx.expected_type.as_ref().filter(|it| !it.is_unknown())?;
+    if !func.ret_type(ctx.db).could_unify_with(expected_ty) {
+        return None;
+    }
+    mark::hit!(boosts_ufcs_methods_returning_expected_type);
+    Some(CompletionScore::TypeMatch)
+}
+
+/// Fields are often typed by the start of their words, like `mF` or `myField`
+/// for `my_field`, which a plain prefix match misses.
+fn matches_typed_words(ctx: &CompletionContext, name: &str) -> bool {
+    let typed = ctx.typed_text();
+    if typed.is_empty() || name.starts_with(typed) || !matches_word_starts(typed, name) {
+        return false;
+    }
+    mark::hit!(boosts_fields_matching_typed_words);
+    true
+}
+
+/// Boosts consts which have the type of the const parameter we are completing
+/// an argument for, like `LEN: usize` in `Arr::<<|>>`, or of an array length.
+fn const_arg_score(ctx: &CompletionContext, constant: hir::Const) -> Option<CompletionScore> {
+    let expected_ty = ctx.expected_const_type()?;
+    let ty = constant.ty(ctx.db);
+    if ty.is_unknown() || !ty.could_unify_with(&expected_ty) {
+        return None;
+    }
+    mark::hit!(boosts_consts_of_const_param_type);
+    Some(CompletionScore::TypeMatch)
+}
+
+fn range_bound_score(ctx: &CompletionContext, constant: hir::Const) -> Option<CompletionScore> {
+    let pat_ty = ctx.range_pat_ty.as_ref()?;
+    let ty = constant.ty(ctx.db);
+    if ty.is_unknown() || pat_ty.is_unknown() || !ty.could_unify_with(pat_ty) {
+        return None;
+    }
+    mark::hit!(boosts_consts_of_range_bound_type);
+    Some(CompletionScore::TypeMatch)
+}
+
+/// Imports the trait of a method which is not in scope, like `StrExt` for
+/// `"".shout()`, as its method can't be called otherwise.
+fn trait_import_edit(ctx: &CompletionContext, func: hir::Function) -> Option<ImportEdit> {
+    if !ctx.config.enable_autoimport_completions || ctx.dot_receiver.is_none() {
+        return None;
+    }
+    let trait_ = match func.as_assoc_item(ctx.db)?.container(ctx.db) {
+        AssocItemContainer::Trait(it) => it,
+        AssocItemContainer::ImplDef(_) => return None,
+    };
+    if ctx.scope().traits_in_scope().into_iter().any(|it| hir::Trait::from(it) == trait_) {
+        return None;
+    }
+    let path = ctx.scope().module()?.find_use_path(ctx.db, hir::ModuleDef::Trait(trait_))?;
+    mark::hit!(imports_traits_of_methods);
+    let edit = use_statement_edit(&ctx.token.parent(), &path, &ctx.sema);
+    Some(ImportEdit { import_path: path.to_string(), edit })
+}
+
+/// Constructors are only boosted inside `Err(<|>)`, where they build the
+/// expected error type.
+fn compute_ctor_score(ctx: &CompletionContext, ty: &Type) -> Option<CompletionScore> {
+    ctx.expected_err_type.as_ref()?;
+    compute_score(ctx, ty, "")
+}
+
 enum Params {
     Named(Vec<String>),
     Anonymous(usize),
@@ -378,8 +1103,32 @@ impl Params {
 }
 
 impl Builder {
-    fn add_call_parens(mut self, ctx: &CompletionContext, name: String, params: Params) -> Builder {
-        if !ctx.config.add_call_parenthesis {
+    fn add_call_parens(
+        self,
+        ctx: &CompletionContext,
+        kind: CompletionItemKind,
+        name: String,
+        params: Params,
+    ) -> Builder {
+        self.add_call_parens_with_suffix(ctx, kind, name, "", 0, params, "")
+    }
+
+    /// Like `add_call_parens`, but also appends `suffix` (like `.await`) after
+    /// the closing parenthesis, and a turbofish with `type_args` placeholders
+    /// after the name. `generics`, like `<…>`, is shown in the label only.
+    #[allow(clippy::too_many_arguments)]
+    fn add_call_parens_with_suffix(
+        mut self,
+        ctx: &CompletionContext,
+        kind: CompletionItemKind,
+        name: String,
+        generics: &str,
+        type_args: usize,
+        params: Params,
+        suffix: &str,
+    ) -> Builder {
+        let call_parens = ctx.config.call_parens(kind);
+        if !call_parens.add_parens {
             return self;
         }
         if ctx.use_item_syntax.is_some() || ctx.is_call {
@@ -396,42 +1145,490 @@ impl Builder {
         }
 
         let cap = match ctx.config.snippet_cap {
-            Some(it) => it,
+            Some(it) => Some(it),
+            None if ctx.config.report_cursor_offsets => None,
             None => return self,
         };
         // If not an import, add parenthesis automatically.
         mark::hit!(inserts_parens_for_function_calls);
 
+        // Type argument placeholders come first, arguments are numbered after them.
+        let type_args = if cap.is_some() { type_args } else { 0 };
+        let callee = if type_args > 0 {
+            mark::hit!(inserts_turbofish_for_uninferable_type_params);
+            let placeholders = (1..=type_args).map(|idx| format!("${}", idx)).sep_by(", ");
+            format!("{}::<{}>", name, placeholders)
+        } else {
+            name.clone()
+        };
         let (snippet, label) = if params.is_empty() {
-            (format!("{}()$0", name), format!("{}()", name))
+            (format!("{}(){}$0", callee, suffix), format!("{}{}(){}", name, generics, suffix))
         } else {
             self = self.trigger_call_info();
-            let snippet = match (ctx.config.add_call_argument_snippets, params) {
+            // Argument placeholders would be inserted as text without snippets.
+            let add_argument_snippets = call_parens.add_argument_snippets && cap.is_some();
+            let snippet = match (add_argument_snippets, params) {
                 (true, Params::Named(params)) => {
                     let function_params_snippet = params
                         .iter()
                         .enumerate()
-                        .map(|(index, param_name)| format!("${{{}:{}}}", index + 1, param_name))
+                        .map(|(index, param_name)| {
+                            format!("${{{}:{}}}", type_args + index + 1, param_name)
+                        })
                         .sep_by(", ");
-                    format!("{}({})$0", name, function_params_snippet)
+                    format!("{}({}){}$0", callee, function_params_snippet, suffix)
                 }
                 _ => {
                     mark::hit!(suppress_arg_snippets);
-                    format!("{}($0)", name)
+                    format!("{}($0){}", callee, suffix)
                 }
             };
 
-            (snippet, format!("{}(…)", name))
+            (snippet, format!("{}{}(…){}", name, generics, suffix))
         };
-        self.lookup_by(name).label(label).insert_snippet(cap, snippet)
+        let builder = self.lookup_by(name).label(label);
+        match cap {
+            Some(cap) => builder.insert_snippet(cap, snippet),
+            None => builder.insert_snippet_as_text(&snippet),
+        }
     }
 }
 
-fn is_deprecated(node: impl HasAttrs, db: &RootDatabase) -> bool {
-    node.attrs(db).by_key("deprecated").exists()
-}
-
-fn guess_macro_braces(macro_name: &str, docs: &str) -> (&'static str, &'static str) {
+/// The number of type arguments to spell out in a call of `fn_def`, if some of
+/// them can't be inferred from the arguments, like in `"92".parse::<u8>()`.
+fn uninferable_type_param_count(fn_def: &ast::FnDef) -> usize {
+    let type_params: Vec<ast::TypeParam> =
+        fn_def.type_param_list().into_iter().flat_map(|it| it.type_params()).collect();
+    let param_types: Vec<ast::TypeRef> = fn_def
+        .param_list()
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|it| it.ascribed_type())
+        .collect();
+    // Explicit type arguments are not allowed with `impl Trait` arguments.
+    let has_impl_trait = param_types
+        .iter()
+        .any(|ty| ty.syntax().descendants().any(|it| it.kind() == SyntaxKind::IMPL_TRAIT_TYPE));
+    let has_const_params =
+        fn_def.type_param_list().map_or(false, |it| it.const_params().next().is_some());
+    if has_impl_trait || has_const_params {
+        return 0;
+    }
+    let is_inferable = |type_param: &ast::TypeParam| {
+        let name = match type_param.name() {
+            Some(it) => it.text().clone(),
+            None => return true,
+        };
+        param_types.iter().any(|ty| {
+            ty.syntax()
+                .descendants_with_tokens()
+                .filter_map(|it| it.into_token())
+                .any(|it| it.kind() == SyntaxKind::IDENT && it.text() == &name)
+        })
+    };
+    if type_params.iter().all(is_inferable) {
+        0
+    } else {
+        type_params.len()
+    }
+}
+
+/// Names of the type parameters which have to be spelled out, as defaulted
+/// ones can be omitted.
+fn non_default_type_param_names(db: &RootDatabase, def: hir::GenericDef) -> Vec<String> {
+    def.params(db)
+        .into_iter()
+        .filter(|it| it.default(db).map_or(true, |ty| ty.is_unknown()))
+        .map(|it| it.name(db).to_string())
+        .collect()
+}
+
+/// Whether `fn_def` has generic parameters other than lifetimes.
+fn has_type_or_const_params(fn_def: &ast::FnDef) -> bool {
+    fn_def
+        .type_param_list()
+        .map_or(false, |it| it.type_params().next().is_some() || it.const_params().next().is_some())
+}
+
+/// The number of steps from `from` to `to` in the module tree, going up to
+/// their closest common ancestor and down again.
+fn module_distance(db: &RootDatabase, from: hir::Module, to: hir::Module) -> u32 {
+    let mut from_path = from.path_to_root(db);
+    let mut to_path = to.path_to_root(db);
+    while from_path.last().is_some() && from_path.last() == to_path.last() {
+        from_path.pop();
+        to_path.pop();
+    }
+    (from_path.len() + to_path.len()) as u32
+}
+
+/// The impl of the trait of `func` for `self_ty`. Only the crates of the
+/// trait and of `self_ty` are searched, which is where the orphan rules put
+/// nearly all impls.
+fn trait_impl_for(
+    ctx: &CompletionContext,
+    func: hir::Function,
+    self_ty: &Type,
+) -> Option<hir::ImplDef> {
+    let trait_ = match func.as_assoc_item(ctx.db)?.container(ctx.db) {
+        AssocItemContainer::Trait(it) => it,
+        AssocItemContainer::ImplDef(_) => return None,
+    };
+    let trait_crate = trait_.module(ctx.db).krate();
+    let self_crate = self_ty.as_adt().map(|it| it.module(ctx.db).krate());
+    iter::once(trait_crate)
+        .chain(self_crate.filter(|it| *it != trait_crate))
+        .flat_map(|krate| hir::ImplDef::for_trait(ctx.db, krate, trait_))
+        .find(|it| it.target_ty(ctx.db).could_unify_with(self_ty))
+}
+
+/// Renders the parameter and return types of `function_signature` from the
+/// types of a trait method called on a known `Self`, like `u32` for
+/// `Self::Item`.
+fn render_self_sig(
+    ctx: &CompletionContext,
+    fn_def: &ast::FnDef,
+    function_signature: &mut FunctionSignature,
+    param_tys: &[Type],
+    ret_ty: &Type,
+) {
+    let param_list = match fn_def.param_list() {
+        Some(it) => it,
+        None => return,
+    };
+    let mut param_tys = param_tys.iter();
+    let mut params = function_signature.parameters.iter_mut();
+    if let Some(self_param) = param_list.self_param() {
+        let (param, ty) = match (params.next(), param_tys.next()) {
+            (Some(param), Some(ty)) => (param, ty),
+            _ => return,
+        };
+        // `self` and `&self` don't mention `Self`, only `self: Box<Self>` does.
+        if self_param.ascribed_type().is_some() {
+            let mut_ = if self_param.mut_token().is_some() { "mut " } else { "" };
+            *param = format!("{}self: {}", mut_, ty.display(ctx.db));
+        }
+    }
+    for ((param, ty), ast_param) in params.zip(param_tys).zip(param_list.params()) {
+        if let Some(pat) = ast_param.pat() {
+            *param = format!("{}: {}", pat.syntax().text(), ty.display(ctx.db));
+        }
+    }
+    if function_signature.ret_type.is_some() {
+        function_signature.ret_type = Some(ret_ty.display(ctx.db).to_string());
+    }
+}
+
+/// The text of `receiver` if it is a tuple field access, like `pair.0`. While
+/// the field after it is typed, the receiver can be lexed as `pair` and the
+/// float `0.`, so the trailing dot is stripped.
+fn tuple_index_receiver_text(receiver: &ast::Expr) -> Option<String> {
+    let field_expr = match receiver {
+        ast::Expr::FieldExpr(it) => it,
+        _ => return None,
+    };
+    let last = field_expr.syntax().last_token()?;
+    let text = field_expr.syntax().text().to_string();
+    match last.kind() {
+        SyntaxKind::INT_NUMBER => Some(text),
+        SyntaxKind::FLOAT_NUMBER if last.text().ends_with('.') => {
+            Some(text.trim_end_matches('.').to_string())
+        }
+        _ => None,
+    }
+}
+
+fn adt_generic_params_with_defaults(db: &RootDatabase, adt: hir::Adt) -> Option<String> {
+    match adt {
+        hir::Adt::Struct(it) => generic_params_with_defaults(&it.source(db).value),
+        hir::Adt::Union(it) => generic_params_with_defaults(&it.source(db).value),
+        hir::Adt::Enum(it) => generic_params_with_defaults(&it.source(db).value),
+    }
+}
+
+fn scope_def_attrs(db: &RootDatabase, resolution: &ScopeDef) -> Option<hir::Attrs> {
+    let def: hir::AttrDef = match *resolution {
+        ScopeDef::ModuleDef(hir::ModuleDef::Module(it)) => it.into(),
+        ScopeDef::ModuleDef(hir::ModuleDef::Adt(it)) => it.into(),
+        ScopeDef::ModuleDef(hir::ModuleDef::Const(it)) => it.into(),
+        ScopeDef::ModuleDef(hir::ModuleDef::Static(it)) => it.into(),
+        ScopeDef::ModuleDef(hir::ModuleDef::Trait(it)) => it.into(),
+        ScopeDef::ModuleDef(hir::ModuleDef::TypeAlias(it)) => it.into(),
+        _ => return None,
+    };
+    Some(def.attrs(db))
+}
+
+/// The ABI of the `extern` block `fn_def` is declared in, like `extern "C"`.
+fn extern_block_abi(fn_def: &ast::FnDef) -> Option<String> {
+    let block = fn_def.syntax().ancestors().nth(2).and_then(ast::ExternBlock::cast)?;
+    Some(block.abi().map_or_else(|| "extern".to_string(), |it| it.to_string()))
+}
+
+/// Describes the feature gate of an `#[unstable(feature = "...")]` item.
+fn unstable_note(attrs: &hir::Attrs) -> Option<String> {
+    let unstable = attrs.by_key("unstable");
+    if !unstable.exists() {
+        return None;
+    }
+    let note = match unstable.key_value("feature") {
+        Some(feature) => format!("unstable: {}", feature),
+        None => "unstable".to_string(),
+    };
+    Some(note)
+}
+
+/// Describes `#[deprecated(since = "...", note = "...")]`. The version and
+/// the note are kept apart, like `deprecated since 1.0.0` and `use bar`.
+fn deprecation_notes(attrs: &hir::Attrs) -> Vec<String> {
+    let deprecated = attrs.by_key("deprecated");
+    let mut notes = Vec::new();
+    if let Some(since) = deprecated.key_value("since") {
+        notes.push(format!("deprecated since {}", since));
+    }
+    // `#[deprecated = "..."]` is a shorthand for the note.
+    let note = deprecated.key_value("note").or_else(|| deprecated.string_value().cloned());
+    notes.extend(note.map(|it| it.to_string()));
+    notes
+}
+
+/// Checks for `#[doc(hidden)]` items from other crates, which are not meant
+/// to be used directly even though they are public.
+fn is_hidden_from_docs(
+    ctx: &CompletionContext,
+    attrs: &hir::Attrs,
+    module: Option<hir::Module>,
+) -> bool {
+    if !ctx.config.respect_doc_hidden {
+        return false;
+    }
+    let is_local = module.map_or(false, |it| Some(it.krate()) == ctx.krate);
+    !is_local
+        && attrs
+            .by_key("doc")
+            .tt_values()
+            .any(|tt| tt.token_trees.iter().any(|it| it.to_string() == "hidden"))
+}
+
+/// Methods callable on a value of type `ty`, used as "what can I do next"
+/// hints. Inherent methods come first, as method resolution visits them
+/// before trait methods.
+fn follow_up_methods(ctx: &CompletionContext, ty: &Type) -> Vec<String> {
+    const MAX_FOLLOW_UPS: usize = 5;
+
+    let mut res: Vec<String> = Vec::new();
+    let krate = match ctx.krate {
+        Some(it) => it,
+        None => return res,
+    };
+    if ty.is_unknown() {
+        return res;
+    }
+    let traits_in_scope = ctx.scope().traits_in_scope();
+    ty.iterate_method_candidates(ctx.db, krate, &traits_in_scope, None, |_ty, func| {
+        let name = func.name(ctx.db).to_string();
+        if func.has_self_param(ctx.db)
+            && ctx.scope().module().map_or(true, |m| func.is_visible_from(ctx.db, m))
+            && !res.contains(&name)
+        {
+            res.push(name);
+        }
+        if res.len() == MAX_FOLLOW_UPS {
+            Some(())
+        } else {
+            None
+        }
+    });
+    res
+}
+
+/// How a method takes its receiver. Typed receivers, like `self: &Self`, are
+/// classified by their type.
+fn self_param_kind(self_param: &ast::SelfParam) -> ast::SelfParamKind {
+    match self_param.ascribed_type() {
+        Some(ast::TypeRef::ReferenceType(it)) if it.mut_token().is_some() => {
+            ast::SelfParamKind::MutRef
+        }
+        Some(ast::TypeRef::ReferenceType(_)) => ast::SelfParamKind::Ref,
+        Some(_) => ast::SelfParamKind::Owned,
+        None => self_param.kind(),
+    }
+}
+
+fn self_kind_note(self_param: &ast::SelfParam) -> &'static str {
+    match self_param_kind(self_param) {
+        ast::SelfParamKind::Owned => "by value",
+        ast::SelfParamKind::Ref => "by ref",
+        ast::SelfParamKind::MutRef => "by mut ref",
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TypeLayout {
+    size: u64,
+    align: u64,
+}
+
+/// Computes the size and alignment of `ty` on a 64-bit target, or `None` for
+/// types we can't lay out, like generic parameters or arrays.
+///
+/// FIXME: this assumes the default representation, which reorders fields by
+/// alignment, and ignores `#[repr]` and niches.
+fn type_layout(db: &RootDatabase, ty: &Type, depth: usize) -> Option<TypeLayout> {
+    const POINTER: TypeLayout = TypeLayout { size: 8, align: 8 };
+    if depth > 8 {
+        return None;
+    }
+    if let Some(pointee) = ty.remove_ref() {
+        // Pointers to `str`, slices and trait objects also store a length or vtable.
+        let is_fat = pointee.as_builtin() == Some(BuiltinType::Str)
+            || pointee.is_slice()
+            || pointee.as_dyn_trait().is_some();
+        return Some(if is_fat { TypeLayout { size: 16, align: 8 } } else { POINTER });
+    }
+    if ty.is_raw_ptr() || ty.is_fn() {
+        return Some(POINTER);
+    }
+    if let Some(adt) = ty.as_adt() {
+        // `repr` attributes change the layout in ways we don't model.
+        if adt.attrs(db).by_key("repr").exists() {
+            return None;
+        }
+        return match adt {
+            hir::Adt::Struct(_) => {
+                let fields = ty.fields(db).into_iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+                aggregate_layout(db, &fields, depth)
+            }
+            hir::Adt::Enum(it) => {
+                let variants = it.variants(db);
+                let is_fieldless = variants.iter().all(|it| it.fields(db).is_empty());
+                match variants.len() {
+                    0 | 1 if is_fieldless => Some(TypeLayout { size: 0, align: 1 }),
+                    2..=256 if is_fieldless => Some(TypeLayout { size: 1, align: 1 }),
+                    _ => None,
+                }
+            }
+            hir::Adt::Union(_) => None,
+        };
+    }
+    let tuple_fields = ty.tuple_fields(db);
+    if !tuple_fields.is_empty() {
+        return aggregate_layout(db, &tuple_fields, depth);
+    }
+    if ty.is_unit() {
+        return Some(TypeLayout { size: 0, align: 1 });
+    }
+    let (size, align) = match ty.as_builtin()? {
+        BuiltinType::Bool => (1, 1),
+        BuiltinType::Char => (4, 4),
+        BuiltinType::Str => return None,
+        BuiltinType::Int(it) => match it.bitness {
+            IntBitness::X8 => (1, 1),
+            IntBitness::X16 => (2, 2),
+            IntBitness::X32 => (4, 4),
+            IntBitness::X64 | IntBitness::Xsize => (8, 8),
+            IntBitness::X128 => (16, 16),
+        },
+        BuiltinType::Float(it) => match it.bitness {
+            FloatBitness::X32 => (4, 4),
+            FloatBitness::X64 => (8, 8),
+        },
+    };
+    Some(TypeLayout { size, align })
+}
+
+/// Lays out struct or tuple fields, sorted by decreasing alignment so that
+/// there is padding only at the end.
+fn aggregate_layout(db: &RootDatabase, fields: &[Type], depth: usize) -> Option<TypeLayout> {
+    let mut size = 0;
+    let mut align = 1;
+    for field in fields {
+        let layout = type_layout(db, field, depth + 1)?;
+        size += layout.size;
+        align = align.max(layout.align);
+    }
+    let padding = (align - size % align) % align;
+    Some(TypeLayout { size: size + padding, align })
+}
+
+/// Checks if `def` is implicitly in scope through the prelude, when
+/// `CompletionConfig::deprioritize_prelude` is set.
+fn is_prelude_item(ctx: &CompletionContext, def: hir::ModuleDef) -> bool {
+    ctx.prelude_defs.contains(&def)
+}
+
+/// The visibility of an item, like `pub(crate)`, if
+/// `CompletionConfig::show_visibility` is set. Restricted visibilities are
+/// rendered as written, as `pub(super)` and `pub(crate)` may resolve to the
+/// same module.
+fn visibility_note(ctx: &CompletionContext, def: hir::ModuleDef) -> Option<String> {
+    if !ctx.config.show_visibility {
+        return None;
+    }
+    let note = match def.definition_visibility(ctx.db)? {
+        hir::Visibility::Public => "pub".to_string(),
+        hir::Visibility::Module(_) => match visibility_syntax(ctx, def) {
+            Some(it) => it.syntax().text().to_string(),
+            None => "private".to_string(),
+        },
+    };
+    Some(note)
+}
+
+fn visibility_syntax(ctx: &CompletionContext, def: hir::ModuleDef) -> Option<ast::Visibility> {
+    use ast::VisibilityOwner;
+    use hir::{Adt, ModuleDef};
+
+    match def {
+        ModuleDef::Module(it) => it.declaration_source(ctx.db)?.value.visibility(),
+        ModuleDef::Function(it) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Adt(Adt::Struct(it)) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Adt(Adt::Union(it)) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Adt(Adt::Enum(it)) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Const(it) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Static(it) => it.source(ctx.db).value.visibility(),
+        ModuleDef::Trait(it) => it.source(ctx.db).value.visibility(),
+        ModuleDef::TypeAlias(it) => it.source(ctx.db).value.visibility(),
+        ModuleDef::EnumVariant(_) | ModuleDef::BuiltinType(_) => None,
+    }
+}
+
+/// Where an item is defined, if `CompletionConfig::show_item_origin` is set.
+fn item_origin(ctx: &CompletionContext, module: hir::Module) -> Option<String> {
+    if !ctx.config.show_item_origin {
+        return None;
+    }
+    let path = determine_mod_path(ctx.db, module, None);
+    if path.is_empty() {
+        return None;
+    }
+    Some(format!("from {}", path))
+}
+
+/// The trait an associated type belongs to, like `Iterator` for `type Item`,
+/// also if it is defined in an impl of the trait.
+fn providing_trait_name(db: &RootDatabase, type_alias: hir::TypeAlias) -> Option<String> {
+    match type_alias.as_assoc_item(db)?.container(db) {
+        AssocItemContainer::Trait(it) => Some(it.name(db).to_string()),
+        AssocItemContainer::ImplDef(it) => match it.source(db).value.target_trait()? {
+            ast::TypeRef::PathType(it) => Some(it.path()?.segment()?.name_ref()?.to_string()),
+            _ => None,
+        },
+    }
+}
+
+fn with_note(detail: Option<String>, note: &str) -> String {
+    match detail {
+        Some(detail) => format!("{} ({})", detail, note),
+        None => format!("({})", note),
+    }
+}
+
+fn is_deprecated(node: impl HasAttrs, db: &RootDatabase) -> bool {
+    node.attrs(db).by_key("deprecated").exists()
+}
+
+fn guess_macro_braces(macro_name: &str, docs: &str) -> (&'static str, &'static str) {
     let mut votes = [0, 0, 0];
     for (idx, s) in docs.match_indices(&macro_name) {
         let (before, after) = (&docs[..idx], &docs[idx + s.len()..]);
@@ -461,18 +1658,33 @@ fn guess_macro_braces(macro_name: &str, docs: &str) -> (&'static str, &'static s
 
 #[cfg(test)]
 mod tests {
+    use expect::{expect, Expect};
     use insta::assert_debug_snapshot;
     use test_utils::mark;
 
     use crate::completion::{
-        test_utils::{check_edit, check_edit_with_config, do_completion},
-        CompletionConfig, CompletionItem, CompletionKind,
+        test_utils::{
+            check_edit, check_edit_with_config, completion_list, completion_list_with_options,
+            do_completion, do_completion_with_options, get_all_completion_items,
+        },
+        CallParens, CallParensPerKind, CompletionConfig, CompletionItem, CompletionItemKind,
+        CompletionKind, CompletionRelevance, MemberPreference,
     };
 
     fn do_reference_completion(ra_fixture: &str) -> Vec<CompletionItem> {
         do_completion(ra_fixture, CompletionKind::Reference)
     }
 
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
+    fn check_with_config(ra_fixture: &str, config: CompletionConfig, expect: Expect) {
+        let actual = completion_list_with_options(ra_fixture, CompletionKind::Reference, &config);
+        expect.assert_eq(&actual);
+    }
+
     #[test]
     fn enum_detail_includes_names_for_record() {
         assert_debug_snapshot!(
@@ -556,6 +1768,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trait_detail_includes_super_traits() {
+        check(
+            r#"
+trait PartialEq {}
+trait Eq: PartialEq {}
+trait PartialOrd: PartialEq {}
+trait Ord: Eq + PartialOrd {}
+fn foo<T: <|>>() {}
+"#,
+            expect![[r#"
+                tt Eq trait Eq: PartialEq
+                tt Ord trait Ord: Eq + PartialOrd
+                tt PartialEq
+                tt PartialOrd trait PartialOrd: PartialEq
+            "#]],
+        );
+    }
+
+    #[test]
+    fn enum_detail_includes_explicit_discriminant() {
+        check(
+            r#"
+enum Ordering { Less = -1, Equal = 0, Greater }
+fn main() { Ordering::<|> }
+"#,
+            expect![[r#"
+                ev Equal () = 0
+                ev Greater ()
+                ev Less () = -1
+            "#]],
+        );
+    }
+
     #[test]
     fn sets_deprecated_flag_in_completion_items() {
         assert_debug_snapshot!(
@@ -598,37 +1844,678 @@ mod tests {
                 insert: "something_else_deprecated()$0",
                 kind: Function,
                 lookup: "something_else_deprecated",
-                detail: "fn something_else_deprecated()",
+                detail: "fn something_else_deprecated() (deprecated since 1.0.0)",
                 deprecated: true,
             },
         ]
         "###
         );
 
-        assert_debug_snapshot!(do_reference_completion(
+        assert_debug_snapshot!(do_reference_completion(
+            r#"
+struct A {
+    #[deprecated]
+    the_field: u32,
+}
+fn foo() {
+   A { the<|> }
+}
+"#,
+        ),
+        @r###"
+        [
+            CompletionItem {
+                label: "the_field",
+                source_range: 69..72,
+                delete: 69..72,
+                insert: "the_field",
+                kind: Field,
+                detail: "u32",
+                deprecated: true,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn generic_defaults_in_detail() {
+        check(
+            r#"
+struct Buf<const N: usize = 16>;
+fn main() { let _: B<|> }
+"#,
+            expect![[r#"
+                st Buf Buf<const N: usize = 16>
+                fn main() fn main()
+            "#]],
+        );
+        check(
+            r#"
+struct RandomState;
+struct HashMap<K, V, S = RandomState>(K, V, S);
+fn main() { let _: H<|> }
+"#,
+            expect![[r#"
+                st HashMap<…> HashMap<K, V, S = RandomState>
+                st RandomState
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_generics_in_function_labels() {
+        check(
+            r#"
+fn foo<T>() {}
+fn bar<'a>(_: &'a u8) {}
+fn baz<const N: usize>(_: [u8; N]) {}
+fn main() { f<|> }
+"#,
+            expect![[r#"
+                fn bar(…) fn bar<'a>(_: &'a u8)
+                fn baz<…>(…) fn baz(_: [u8; N])
+                fn foo<…>() fn foo<T>()
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn suggests_await_and_try_for_calls() {
+        mark::check!(suggests_await_and_try_for_calls);
+        check(
+            r#"
+//- /main.rs
+use core::result::Result;
+async fn fetch() -> Result<u32, ()> { Result::Ok(92) }
+async fn main() -> Result<(), ()> { fe<|> }
+//- /core/lib.rs
+pub mod future {
+    #[lang = "future_trait"]
+    pub trait Future { type Output; }
+}
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+"#,
+            expect![[r#"
+                en Result
+                md core
+                fn fetch() async fn fetch() -> Result<u32, ()>
+                fn fetch().await? async fn fetch() -> Result<u32, ()>
+                fn main() async fn main() -> Result<(), ()>
+                fn main().await? async fn main() -> Result<(), ()>
+            "#]],
+        );
+        check_edit(
+            "fetch.await?",
+            r#"
+//- /main.rs
+use core::result::Result;
+async fn fetch(id: u32) -> Result<u32, ()> { Result::Ok(id) }
+async fn main() -> Result<(), ()> { fe<|> }
+//- /core/lib.rs
+pub mod future {
+    #[lang = "future_trait"]
+    pub trait Future { type Output; }
+}
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+"#,
+            r#"
+use core::result::Result;
+async fn fetch(id: u32) -> Result<u32, ()> { Result::Ok(id) }
+async fn main() -> Result<(), ()> { fetch(${1:id}).await?$0 }
+"#,
+        );
+    }
+
+    #[test]
+    fn shows_async_in_labels() {
+        let config = CompletionConfig { show_async_in_labels: true, ..CompletionConfig::default() };
+        let actual = completion_list_with_options(
+            r#"
+struct Client;
+impl Client {
+    async fn fetch(&self, id: u32) -> u32 { id }
+    fn close(&self) {}
+}
+fn main(client: Client) { client.<|> }
+"#,
+            CompletionKind::Reference,
+            &config,
+        );
+        expect![[r#"
+            me close() fn close(&self)
+            me fetch(…) async async fn fetch(&self, id: u32) -> u32
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[test]
+    fn suggests_await_or_try_separately() {
+        check(
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+async fn fetch() -> u32 { 92 }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+async fn main() -> u32 { <|> }
+"#,
+            expect![[r#"
+                en Result
+                fn fetch() async fn fetch() -> u32
+                fn fetch().await async fn fetch() -> u32
+                fn main() async fn main() -> u32
+                fn main().await async fn main() -> u32
+                fn parse() fn parse() -> Result<u32, ()>
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs
+use core::result::Result;
+async fn fetch() -> Result<u32, ()> { Result::Ok(92) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() -> Result<(), ()> { <|> }
+//- /core/lib.rs
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+"#,
+            expect![[r#"
+                en Result
+                md core
+                fn fetch() async fn fetch() -> Result<u32, ()>
+                fn main() fn main() -> Result<(), ()>
+                fn main()? fn main() -> Result<(), ()>
+                fn parse() fn parse() -> Result<u32, ()>
+                fn parse()? fn parse() -> Result<u32, ()>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_try_for_calls_returning_other_types_named_result() {
+        check(
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() -> Result<(), ()> { <|> }
+"#,
+            expect![[r#"
+                en Result
+                fn main() fn main() -> Result<(), ()>
+                fn parse() fn parse() -> Result<u32, ()>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_abi_of_extern_functions() {
+        check(
+            r#"
+extern "C" {
+    fn strlen(s: *const u8) -> usize;
+}
+extern "C" fn callback(data: *mut u8) {}
+fn main() { <|> }
+"#,
+            expect![[r#"
+                fn callback(…) extern "C" fn callback(data: *mut u8)
+                fn main() fn main()
+                fn strlen(…) unsafe extern "C" fn strlen(s: *const u8) -> usize
+            "#]],
+        );
+    }
+
+    #[test]
+    fn deprioritizes_traits_in_inherent_impls() {
+        mark::check!(deprioritizes_traits_in_inherent_impls);
+        let config = CompletionConfig::default();
+        let mut completions = do_completion_with_options(
+            r#"
+//- /main.rs crate:main deps:other
+use other::Remote;
+trait Shape {}
+struct Circle;
+impl <|>
+//- /other.rs crate:other
+pub struct Remote;
+"#,
+            CompletionKind::Reference,
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, ["Circle", "Remote", "Shape"]);
+    }
+
+    #[test]
+    fn sorts_nearby_types_first() {
+        let config = CompletionConfig::default();
+        let mut completions = do_completion_with_options(
+            r#"
+mod shapes {
+    pub mod round {
+        pub struct Ball;
+    }
+}
+use shapes::round::Ball;
+struct Cube;
+fn main() { let _: <|> }
+"#,
+            CompletionKind::Reference,
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, ["Cube", "Ball", "main()", "shapes"]);
+    }
+
+    #[test]
+    fn offers_numeric_conversions() {
+        check_edit(
+            "count.into()",
+            r#"
+//- /main.rs
+use core::convert::From;
+impl From<u32> for u64 { fn from(t: u32) -> u64 { loop {} } }
+fn main() {
+    let count: u32 = 92;
+    let x: u64 = c<|>
+}
+//- /core/lib.rs
+pub mod convert { pub trait From<T> { fn from(t: T) -> Self; } }
+"#,
+            r#"
+use core::convert::From;
+impl From<u32> for u64 { fn from(t: u32) -> u64 { loop {} } }
+fn main() {
+    let count: u32 = 92;
+    let x: u64 = count.into()
+}
+"#,
+        );
+        check(
+            r#"
+fn main() {
+    let count: u32 = 92;
+    let x: i64 = c<|>
+}
+"#,
+            expect![[r#"
+                bn count u32
+                bn count as i64 count: u32
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn skips_lossy_numeric_casts() {
+        mark::check!(skips_lossy_numeric_casts);
+        check(
+            r#"
+fn main() {
+    let count: u64 = 92;
+    let x: u32 = c<|>
+}
+"#,
+            expect![[r#"
+                bn count u64
+                fn main() fn main()
+            "#]],
+        );
+        check(
+            r#"
+fn main() {
+    let count: i32 = 92;
+    let x: f32 = c<|>
+}
+"#,
+            expect![[r#"
+                bn count i32
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn offers_wrapping_values_in_some_or_ok() {
+        check(
+            r#"
+//- /main.rs
+use core::option::Option::{self, *};
+fn parse(text: &str) -> Option<i32> {
+    let value = 92;
+    v<|>
+}
+//- /core/lib.rs
+pub mod option { pub enum Option<T> { None, Some(T) } }
+"#,
+            expect![[r#"
+                ev None ()
+                en Option
+                bn Some(value) value: i32
+                ev Some(…) (T)
+                md core
+                fn parse(…) fn parse(text: &str) -> Option<i32>
+                fn parse(…)? fn parse(text: &str) -> Option<i32>
+                bn text &str
+                bn value i32
+            "#]],
+        );
+        check_edit(
+            "Ok(value)",
+            r#"
+//- /main.rs
+use core::result::Result::{self, *};
+fn parse() -> Result<i32, ()> {
+    let value = 92;
+    return v<|>
+}
+//- /core/lib.rs
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+"#,
+            r#"
+use core::result::Result::{self, *};
+fn parse() -> Result<i32, ()> {
+    let value = 92;
+    return Ok(value)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_wrapping_values_in_local_option_lookalikes() {
+        check(
+            r#"
+enum Option<T> { None, Some(T) }
+use Option::*;
+fn parse(text: &str) -> Option<i32> {
+    let value = 92;
+    v<|>
+}
+"#,
+            expect![[r#"
+                ev None ()
+                en Option
+                ev Some(…) (T)
+                fn parse(…) fn parse(text: &str) -> Option<i32>
+                bn text &str
+                bn value i32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn renders_function_qualifiers_in_canonical_order() {
+        check(
+            r#"
+const unsafe fn from_raw(ptr: *const u8) -> u8 { 0 }
+async unsafe fn fetch() {}
+pub const unsafe extern "C" fn exported() {}
+fn main() { <|> }
+"#,
+            expect![[r#"
+                fn exported() pub const unsafe extern "C" fn exported()
+                fn fetch() async unsafe fn fetch()
+                fn from_raw(…) const unsafe fn from_raw(ptr: *const u8) -> u8
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_mutability_of_statics() {
+        check(
+            r#"
+static mut COUNTER: u32 = 0;
+static NAME: &str = "counter";
+fn main() { C<|> }
+"#,
+            expect![[r#"
+                sc COUNTER static mut COUNTER: u32 = 0;
+                sc NAME static NAME: &str = "counter";
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn annotates_unstable_items() {
+        check(
+            r#"
+#[unstable(feature = "fancy_fn")]
+fn fancy() {}
+#[unstable(feature = "fancy_struct", issue = "92")]
+struct Fancy;
+fn main() { f<|> }
+"#,
+            expect![[r#"
+                st Fancy (unstable: fancy_struct)
+                fn fancy() fn fancy() (unstable: fancy_fn)
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn annotates_deprecation_version_and_note() {
+        check(
+            r#"
+#[deprecated(since = "1.0.0", note = "use `fresh` instead")]
+fn stale() {}
+#[deprecated = "too old"]
+struct Old;
+#[deprecated]
+fn plain() {}
+fn main() { s<|> }
+"#,
+            expect![[r#"
+                st Old (too old)
+                fn main() fn main()
+                fn plain() fn plain()
+                fn stale() fn stale() (deprecated since 1.0.0) (use `fresh` instead)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hides_doc_hidden_items() {
+        mark::check!(hides_doc_hidden_items);
+        check_with_config(
+            r#"
+//- /main.rs crate:main deps:dep
+#[doc(hidden)]
+fn local_hidden() {}
+fn main() { dep::<|> }
+
+//- /dep/lib.rs crate:dep
+pub fn visible() {}
+#[doc(hidden)]
+pub fn __private_api() {}
+#[doc(hidden)]
+pub struct Internals;
+"#,
+            CompletionConfig { respect_doc_hidden: true, ..CompletionConfig::default() },
+            expect![[r#"
+                fn visible() pub fn visible()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hides_unstable_items() {
+        mark::check!(hides_unstable_items);
+        check_with_config(
+            r#"
+#[unstable(feature = "fancy_fn")]
+fn fancy() {}
+fn main() { f<|> }
+"#,
+            CompletionConfig { hide_unstable: true, ..CompletionConfig::default() },
+            expect![[r#"
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn inserts_receiver_for_ufcs_calls() {
+        mark::check!(inserts_receiver_for_ufcs_calls);
+        check_edit(
+            "method",
+            r#"
+trait Trait { fn method(&self, x: u32); }
+fn main() { Trait::<|> }
+"#,
+            r#"
+trait Trait { fn method(&self, x: u32); }
+fn main() { Trait::method(${1:self}, ${2:x})$0 }
+"#,
+        );
+    }
+
+    #[test]
+    fn shows_enum_variant_count() {
+        let config =
+            CompletionConfig { show_enum_variant_count: true, ..CompletionConfig::default() };
+        check_with_config(
+            r#"
+enum Color { Red, Green, Blue }
+enum Single { One }
+fn main() { let _: C<|> }
+"#,
+            config,
+            expect![[r#"
+                en Color (3 variants)
+                en Single (1 variant)
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn notes_self_kind() {
+        check_with_config(
+            r#"
+struct S;
+impl S {
+    fn peek(&self) {}
+    fn poke(&mut self) {}
+    fn consume(self) {}
+    fn typed(self: &Self) {}
+}
+fn foo(s: S) { s.<|> }
+"#,
+            CompletionConfig { show_self_kind: true, ..CompletionConfig::default() },
+            expect![[r#"
+                me consume() fn consume(self) (by value)
+                me peek() fn peek(&self) (by ref)
+                me poke() fn poke(&mut self) (by mut ref)
+                me typed() fn typed(self: &Self) (by ref)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn notes_const_evaluable_calls() {
+        mark::check!(notes_const_evaluable_calls);
+        check_with_config(
             r#"
-struct A {
-    #[deprecated]
-    the_field: u32,
+const fn square(x: u32) -> u32 { x * x }
+fn cube(x: u32) -> u32 { x * x * x }
+const AREA: u32 = <|>;
+"#,
+            CompletionConfig { show_const_eval: true, ..CompletionConfig::default() },
+            expect![[r#"
+                ct AREA
+                fn cube(…) fn cube(x: u32) -> u32
+                fn square(…) const fn square(x: u32) -> u32 (const-evaluable)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_visibility() {
+        check_with_config(
+            r#"
+mod shapes {
+    pub struct Circle;
+    pub(crate) struct Square;
+    pub(super) const SIDES: u32 = 4;
+    struct Private;
+    mod round {
+        pub(in crate::shapes) struct Ball;
+        use super::*;
+        fn area() { <|> }
+    }
 }
-fn foo() {
-   A { the<|> }
+"#,
+            CompletionConfig { show_visibility: true, ..CompletionConfig::default() },
+            expect![[r#"
+                st Ball (pub(in crate::shapes))
+                st Circle (pub)
+                st Private (private)
+                ct SIDES (pub(super))
+                st Square (pub(crate))
+                fn area() fn area() (private)
+                md round (private)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_type_layout() {
+        check_with_config(
+            r#"
+struct Point { x: u8, y: u64, z: u16 }
+struct Name<'a> { text: &'a str, len: usize }
+struct Wrapper<T>(T);
+enum Ordering { Less, Equal, Greater }
+enum Single { Only }
+#[repr(u32)]
+enum Wide { A, B }
+#[repr(C)]
+struct Ffi { a: u8, b: u32, c: u8 }
+fn main() { <|> }
+"#,
+            CompletionConfig { show_type_layout: true, ..CompletionConfig::default() },
+            expect![[r#"
+                st Ffi
+                st Name (size 24, align 8)
+                en Ordering (size 1, align 1)
+                st Point (size 16, align 8)
+                en Single (size 0, align 1)
+                en Wide
+                st Wrapper
+                fn main() fn main()
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shows_item_origin() {
+        check_with_config(
+            r#"
+//- /main.rs crate:main deps:std
+use std::fmt::{Display, format};
+fn main() { <|> }
+
+//- /std/lib.rs crate:std
+pub mod fmt {
+    pub trait Display {}
+    pub fn format() {}
 }
 "#,
-        ),
-        @r###"
-        [
-            CompletionItem {
-                label: "the_field",
-                source_range: 69..72,
-                delete: 69..72,
-                insert: "the_field",
-                kind: Field,
-                detail: "u32",
-                deprecated: true,
-            },
-        ]
-        "###);
+            CompletionConfig { show_item_origin: true, ..CompletionConfig::default() },
+            expect![[r#"
+                tt Display (from std::fmt)
+                fn format() pub fn format() (from std::fmt)
+                fn main() fn main()
+                md std
+            "#]],
+        );
     }
 
     #[test]
@@ -716,6 +2603,60 @@ fn main() { with_args($0) }
         );
     }
 
+    #[test]
+    fn call_parens_per_kind() {
+        let config = CompletionConfig {
+            add_call_argument_snippets: false,
+            call_parens_per_kind: CallParensPerKind {
+                functions: Some(CallParens { add_parens: true, add_argument_snippets: true }),
+                enum_variants: Some(CallParens { add_parens: false, add_argument_snippets: false }),
+                ..CallParensPerKind::default()
+            },
+            ..CompletionConfig::default()
+        };
+        check_edit_with_config(
+            "with_args",
+            r#"
+fn with_args(x: i32, y: String) {}
+fn main() { with_<|> }
+"#,
+            r#"
+fn with_args(x: i32, y: String) {}
+fn main() { with_args(${1:x}, ${2:y})$0 }
+"#,
+            &config,
+        );
+        check_edit_with_config(
+            "Some",
+            r#"
+enum Option<T> { Some(T), None }
+use Option::*;
+fn main() -> Option<i32> { Som<|> }
+"#,
+            r#"
+enum Option<T> { Some(T), None }
+use Option::*;
+fn main() -> Option<i32> { Some }
+"#,
+            &config,
+        );
+        // Methods fall back to the global flags.
+        check_edit_with_config(
+            "foo",
+            r#"
+struct S;
+impl S { fn foo(&self, x: i32) {} }
+fn bar(s: &S) { s.f<|> }
+"#,
+            r#"
+struct S;
+impl S { fn foo(&self, x: i32) {} }
+fn bar(s: &S) { s.foo($0) }
+"#,
+            &config,
+        );
+    }
+
     #[test]
     fn strips_underscores_from_args() {
         check_edit(
@@ -841,6 +2782,116 @@ fn f(foo: &Foo) { foo.foo(); }
         );
     }
 
+    #[test]
+    fn deprioritizes_non_types_in_field_defs() {
+        mark::check!(deprioritizes_non_types_in_field_defs);
+        let completions = do_reference_completion(
+            r"
+            struct Vec<T> {}
+            type Id = u32;
+            const MAX: u32 = 92;
+            fn new() {}
+            mod m {}
+            struct S { x: <|> }
+            ",
+        );
+        assert!(completions.iter().all(|it| it.score().is_none()));
+        let deprioritized: Vec<&str> =
+            completions.iter().filter(|it| it.deprioritized()).map(|it| it.label()).collect();
+        assert_eq!(deprioritized, vec!["MAX", "new()"]);
+
+        check_edit(
+            "Vec",
+            r"
+struct Vec<T> {}
+struct S { x: V<|> }
+",
+            r"
+struct Vec<T> {}
+struct S { x: Vec<${1:T}>$0 }
+",
+        );
+    }
+
+    #[test]
+    fn inserts_turbofish_for_uninferable_type_params() {
+        mark::check!(inserts_turbofish_for_uninferable_type_params);
+        let config = CompletionConfig { add_call_turbofish: true, ..CompletionConfig::default() };
+        check_edit_with_config(
+            "parse",
+            r#"
+struct Str;
+impl Str {
+    fn parse<F>(&self) -> F { loop {} }
+    fn convert<T>(&self, value: T) -> T { value }
+}
+fn main() { Str.<|> }
+"#,
+            r#"
+struct Str;
+impl Str {
+    fn parse<F>(&self) -> F { loop {} }
+    fn convert<T>(&self, value: T) -> T { value }
+}
+fn main() { Str.parse::<$1>()$0 }
+"#,
+            &config,
+        );
+        check_edit_with_config(
+            "convert",
+            r#"
+struct Str;
+impl Str {
+    fn convert<T>(&self, value: T) -> T { value }
+}
+fn main() { Str.<|> }
+"#,
+            r#"
+struct Str;
+impl Str {
+    fn convert<T>(&self, value: T) -> T { value }
+}
+fn main() { Str.convert(${1:value})$0 }
+"#,
+            &config,
+        );
+    }
+
+    #[test]
+    fn inserts_type_param_names_as_placeholders() {
+        check_edit(
+            "HashMap",
+            r"
+struct HashMap<K, V, S = RandomState> {}
+struct RandomState;
+fn foo(xs: Hash<|>)
+",
+            r"
+struct HashMap<K, V, S = RandomState> {}
+struct RandomState;
+fn foo(xs: HashMap<${1:K}, ${2:V}>$0)
+",
+        );
+    }
+
+    #[test]
+    fn hides_generics_in_macro_args() {
+        mark::check!(hides_generics_in_macro_args);
+        check_edit(
+            "Vec",
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* } }
+struct Vec<T> {}
+id! { fn foo(xs: Ve<|>) {} }
+"#,
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* } }
+struct Vec<T> {}
+id! { fn foo(xs: Vec) {} }
+"#,
+        );
+    }
+
     #[test]
     fn inserts_angle_brackets_for_generics() {
         mark::check!(inserts_angle_brackets_for_generics);
@@ -857,7 +2908,7 @@ fn f(foo: &Foo) { foo.foo(); }
                 label: "Vec<…>",
                 source_range: 28..30,
                 delete: 28..30,
-                insert: "Vec<$0>",
+                insert: "Vec<${1:T}>$0",
                 kind: Struct,
                 lookup: "Vec",
             },
@@ -887,7 +2938,7 @@ fn f(foo: &Foo) { foo.foo(); }
                 label: "Vec<…>",
                 source_range: 31..33,
                 delete: 31..33,
-                insert: "Vec<$0>",
+                insert: "Vec<${1:T}>$0",
                 kind: TypeAlias,
                 lookup: "Vec",
             },
@@ -919,6 +2970,7 @@ fn f(foo: &Foo) { foo.foo(); }
                 delete: 35..37,
                 insert: "Vec",
                 kind: Struct,
+                detail: "Vec<T = i128>",
             },
             CompletionItem {
                 label: "foo(…)",
@@ -964,6 +3016,43 @@ fn f(foo: &Foo) { foo.foo(); }
         );
     }
 
+    #[test]
+    fn skips_internal_macro_arms() {
+        check_edit(
+            "log!(…)",
+            r#"
+macro_rules! log {
+    (@prefix) => { "log: " };
+    ($msg:expr) => { println!("{}{}", log!(@prefix), $msg) };
+}
+fn main() { l<|> }
+"#,
+            r#"
+macro_rules! log {
+    (@prefix) => { "log: " };
+    ($msg:expr) => { println!("{}{}", log!(@prefix), $msg) };
+}
+fn main() { log!($0) }
+"#,
+        );
+    }
+
+    #[test]
+    fn no_macro_braces_when_disabled() {
+        check_edit_with_config(
+            "vec!",
+            r#"
+macro_rules! vec { () => {} }
+fn main() { v<|> }
+"#,
+            r#"
+macro_rules! vec { () => {} }
+fn main() { vec! }
+"#,
+            &CompletionConfig { add_macro_braces: false, ..CompletionConfig::default() },
+        );
+    }
+
     #[test]
     fn dont_insert_macro_call_parens_unncessary() {
         mark::check!(dont_insert_macro_call_parens_unncessary);
@@ -1120,6 +3209,416 @@ fn f(foo: &Foo) { foo.foo(); }
         );
     }
 
+    #[test]
+    fn shows_follow_up_methods() {
+        mark::check!(shows_follow_up_methods);
+        let config = CompletionConfig { show_method_chains: true, ..CompletionConfig::default() };
+        let completions = do_completion_with_options(
+            r"
+            struct Builder;
+            impl Builder {
+                fn name(self, name: &str) -> Builder { self }
+                fn size(self, size: u32) -> Builder { self }
+                fn build(self) -> Widget { Widget }
+            }
+            struct Widget;
+            impl Widget {
+                fn show(&self) {}
+            }
+            fn foo(b: Builder) { b.<|> }
+            ",
+            CompletionKind::Reference,
+            &config,
+        );
+        let docs = |label: &str| {
+            let item = completions.iter().find(|it| it.label() == label).unwrap();
+            item.documentation().map(|it| it.as_str().to_string())
+        };
+        assert_eq!(
+            docs("name(…)").as_deref(),
+            Some("Follow-up methods: `name()`, `size()`, `build()`")
+        );
+        assert_eq!(docs("build()").as_deref(), Some("Follow-up methods: `show()`"));
+    }
+
+    #[test]
+    fn reports_cursor_offset_without_snippets() {
+        let mut config =
+            CompletionConfig { report_cursor_offsets: true, ..CompletionConfig::default() };
+        config.allow_snippets(false);
+        assert_debug_snapshot!(
+            do_completion_with_options(
+                r"
+                fn with_args(x: i32, y: String) {}
+                fn main() { with<|> }
+                ",
+                CompletionKind::Reference,
+                &config,
+            ),
+            @r###"
+        [
+            CompletionItem {
+                label: "main()",
+                source_range: 47..51,
+                delete: 47..51,
+                insert: "main()",
+                cursor_offset: 6,
+                kind: Function,
+                lookup: "main",
+                detail: "fn main()",
+            },
+            CompletionItem {
+                label: "with_args(…)",
+                source_range: 47..51,
+                delete: 47..51,
+                insert: "with_args()",
+                cursor_offset: 10,
+                kind: Function,
+                lookup: "with_args",
+                detail: "fn with_args(x: i32, y: String)",
+                trigger_call_info: true,
+            },
+        ]
+        "###
+        );
+    }
+
+    #[test]
+    fn deprioritizes_prelude_items() {
+        let config = CompletionConfig { deprioritize_prelude: true, ..CompletionConfig::default() };
+        let mut completions = do_completion_with_options(
+            r"
+            //- /main.rs crate:main deps:std
+            fn drop_all() {}
+            fn main() { dro<|> }
+
+            //- /std/lib.rs crate:std
+            #[prelude_import]
+            use prelude::v1::*;
+            pub mod prelude {
+                pub mod v1 {
+                    pub use crate::mem::drop;
+                }
+            }
+            pub mod mem {
+                pub fn drop() {}
+            }
+            ",
+            CompletionKind::Reference,
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, ["drop_all()", "main()", "std", "drop()"]);
+    }
+
+    #[test]
+    fn deprioritizes_mut_methods_of_immutable_receivers() {
+        mark::check!(deprioritizes_mut_methods_of_immutable_receivers);
+        let fixture = |receiver: &str| {
+            format!(
+                r"
+                struct Counter {{ n: u32 }}
+                impl Counter {{
+                    fn get(&self) -> u32 {{ self.n }}
+                    fn bump(&mut self) {{ self.n += 1 }}
+                }}
+                fn main(r: &mut Counter) {{
+                    let c = Counter {{ n: 0 }};
+                    let mut m = Counter {{ n: 0 }};
+                    {}.<|>
+                }}
+                ",
+                receiver
+            )
+        };
+        let deprioritized = |receiver: &str, label: &str| {
+            do_completion(&fixture(receiver), CompletionKind::Reference)
+                .into_iter()
+                .find(|it| it.label() == label)
+                .unwrap()
+                .deprioritized()
+        };
+        assert!(deprioritized("c", "bump()"));
+        assert!(!deprioritized("c", "get()"));
+        assert!(!deprioritized("m", "bump()"));
+        assert!(!deprioritized("r", "bump()"));
+    }
+
+    #[test]
+    fn boosts_items_referenced_in_file() {
+        let config =
+            CompletionConfig { boost_referenced_items: true, ..CompletionConfig::default() };
+        let mut completions = do_completion_with_options(
+            r"
+            struct Used;
+            struct Unused;
+            fn make() -> Used { Used }
+            fn main() { let _: <|> }
+            ",
+            CompletionKind::Reference,
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(labels, ["Used", "Unused", "main()", "make()"]);
+    }
+
+    #[test]
+    fn prefers_shorter_labels_in_ties() {
+        let fixture = r"
+            struct Archive;
+            struct Zip;
+            fn main() { let _: <|> }
+            ";
+        let sorted_labels = |config: &CompletionConfig| {
+            let mut completions =
+                do_completion_with_options(fixture, CompletionKind::Reference, config);
+            completions
+                .sort_by_key(|it| it.sort_text(config).unwrap_or_else(|| it.label().to_string()));
+            completions.into_iter().map(|it| it.label().to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted_labels(&CompletionConfig::default()), ["Archive", "Zip", "main()"]);
+        let config =
+            CompletionConfig { prefer_shorter_labels: true, ..CompletionConfig::default() };
+        assert_eq!(sorted_labels(&config), ["Zip", "main()", "Archive"]);
+    }
+
+    #[test]
+    fn prefers_fields_or_methods_by_config() {
+        let fixture = r"
+            struct S { zip: u32 }
+            impl S { fn archive(&self) {} }
+            fn main(s: S) { s.<|> }
+            ";
+        let sorted_labels = |member_preference: MemberPreference| {
+            let config = CompletionConfig { member_preference, ..CompletionConfig::default() };
+            let mut completions =
+                do_completion_with_options(fixture, CompletionKind::Reference, &config);
+            completions
+                .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+            completions.into_iter().map(|it| it.label().to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted_labels(MemberPreference::None), ["archive()", "zip"]);
+        assert_eq!(sorted_labels(MemberPreference::Fields), ["zip", "archive()"]);
+        assert_eq!(sorted_labels(MemberPreference::Methods), ["archive()", "zip"]);
+    }
+
+    #[test]
+    fn explains_relevance_of_type_and_name_match() {
+        let config = CompletionConfig { explain_relevance: true, ..CompletionConfig::default() };
+        let completions = do_completion_with_options(
+            r"
+            fn test(the_field: u32) -> u32 { the_field }
+            fn foo() {
+                let the_field = 92u32;
+                test(<|>)
+            }
+            ",
+            CompletionKind::Reference,
+            &config,
+        );
+        let the_field = completions.iter().find(|it| it.label() == "the_field").unwrap();
+        assert_eq!(
+            the_field.relevance(),
+            Some(CompletionRelevance {
+                type_match: true,
+                name_match: true,
+                is_local: true,
+                is_deprecated: false,
+            })
+        );
+
+        let completions = do_completion(
+            r"
+            fn test(the_field: u32) -> u32 { the_field }
+            fn foo() {
+                let the_field = 92u32;
+                test(<|>)
+            }
+            ",
+            CompletionKind::Reference,
+        );
+        assert!(completions.iter().all(|it| it.relevance().is_none()));
+    }
+
+    #[test]
+    fn boosts_err_conversions() {
+        mark::check!(boosts_err_conversions);
+        let boosted = |ra_fixture: &str| -> Vec<String> {
+            do_completion(ra_fixture, CompletionKind::Reference)
+                .into_iter()
+                .filter(|it| it.score().is_some())
+                .map(|it| it.label().to_string())
+                .collect()
+        };
+        let prelude = r"
+//- /main.rs
+use core::{convert::From, result::Result::{self, *}};
+
+enum MyErr { Io, Parse(u32) }
+struct Other;
+impl From<Other> for MyErr { fn from(_: Other) -> MyErr { MyErr::Io } }
+";
+        let core = r"
+//- /core/lib.rs
+pub mod convert { pub trait From<T> { fn from(t: T) -> Self; } }
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+";
+
+        let actual = boosted(&format!(
+            "{}{}{}",
+            prelude,
+            r"
+fn f() -> Result<(), MyErr> {
+    let other = Other;
+    let err = MyErr::Io;
+    let num = 92u32;
+    Err(<|>)
+}
+",
+            core
+        ));
+        assert_eq!(actual, vec!["MyErr", "Other", "err", "other"]);
+
+        let actual = boosted(&format!(
+            "{}{}{}",
+            prelude,
+            r"
+fn f() -> Result<(), MyErr> { Err(MyErr::<|>) }
+",
+            core
+        ));
+        assert_eq!(actual, vec!["Io", "Parse(…)"]);
+    }
+
+    #[test]
+    fn no_err_conversions_through_other_traits_named_from() {
+        let completions = do_completion(
+            r"
+//- /main.rs
+use core::result::Result::{self, *};
+
+trait From<T> { fn from(t: T) -> Self; }
+enum MyErr { Io }
+struct Other;
+impl From<Other> for MyErr { fn from(_: Other) -> MyErr { MyErr::Io } }
+
+fn f() -> Result<(), MyErr> {
+    let other = Other;
+    Err(<|>)
+}
+//- /core/lib.rs
+pub mod result { pub enum Result<T, E> { Ok(T), Err(E) } }
+",
+            CompletionKind::Reference,
+        );
+        let other = completions.iter().find(|it| it.label() == "other").unwrap();
+        assert_eq!(other.score(), None);
+    }
+
+    #[test]
+    fn boosts_methods_matching_outer_type() {
+        mark::check!(boosts_methods_matching_outer_type);
+        let completions = do_completion(
+            r"
+            struct Vec;
+            impl Vec {
+                fn len(&self) -> usize { 0 }
+                fn is_empty(&self) -> bool { true }
+            }
+            fn foo(v: Vec) {
+                let n: usize = v.<|>
+            }
+            ",
+            CompletionKind::Reference,
+        );
+        let boosted: Vec<&str> =
+            completions.iter().filter(|it| it.score().is_some()).map(|it| it.label()).collect();
+        assert_eq!(boosted, vec!["len()"]);
+    }
+
+    #[test]
+    fn boosts_closure_return_type() {
+        mark::check!(boosts_closure_return_type);
+        let boosted = |ra_fixture: &str| -> Vec<String> {
+            do_completion(ra_fixture, CompletionKind::Reference)
+                .into_iter()
+                .filter(|it| it.score().is_some())
+                .map(|it| it.label().to_string())
+                .collect()
+        };
+        assert_eq!(
+            boosted(
+                r#"
+                fn call(f: impl Fn() -> u32) {}
+                fn main() {
+                    let count = 92u32;
+                    let name = "";
+                    call(|| <|>);
+                }
+                "#
+            ),
+            vec!["count"]
+        );
+        assert_eq!(
+            boosted(
+                r#"
+                struct Name;
+                fn call<F>(f: F) where F: FnOnce() -> Name {}
+                fn main() {
+                    let count = 92u32;
+                    let name = Name;
+                    call(|| { let _ = 1; n<|> });
+                }
+                "#
+            ),
+            vec!["name"]
+        );
+    }
+
+    #[test]
+    fn boosts_self_members() {
+        mark::check!(boosts_self_members);
+        let config = CompletionConfig::default();
+        let mut completions = get_all_completion_items(
+            r"
+            struct S { width: u32 }
+            impl S {
+                fn area(&self) -> u32 { self.<|> }
+            }
+            ",
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions.iter().map(|it| it.label()).take(4).collect();
+        assert_eq!(labels, ["area()", "width", "box", "call"]);
+    }
+
+    #[test]
+    fn boosts_fields_matching_typed_words() {
+        mark::check!(boosts_fields_matching_typed_words);
+        let config = CompletionConfig::default();
+        let mut completions = get_all_completion_items(
+            r"
+            struct S { manifest: u32, mf: u32, my_field: u32 }
+            fn foo(s: S) { s.mF<|> }
+            ",
+            &config,
+        );
+        completions
+            .sort_by_key(|it| it.sort_text(&config).unwrap_or_else(|| it.label().to_string()));
+        let labels: Vec<&str> = completions
+            .iter()
+            .filter(|it| it.kind() == Some(CompletionItemKind::Field))
+            .map(|it| it.label())
+            .collect();
+        assert_eq!(labels, ["my_field", "manifest", "mf"]);
+    }
+
     #[test]
     fn test_struct_field_completion_in_record_lit() {
         mark::check!(test_struct_field_completion_in_record_lit);
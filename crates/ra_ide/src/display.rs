@@ -12,7 +12,7 @@ use ra_syntax::{
     ast::{self, AstNode, AttrsOwner, NameOwner, TypeParamsOwner},
     SyntaxKind::{ATTR, COMMENT},
 };
-use stdx::format_to;
+use stdx::{format_to, SepBy};
 
 pub use function_signature::FunctionSignature;
 pub use navigation_target::NavigationTarget;
@@ -36,6 +36,17 @@ pub(crate) fn const_label(node: &ast::ConstDef) -> String {
     label.trim().to_owned()
 }
 
+pub(crate) fn static_label(node: &ast::StaticDef) -> String {
+    let label: String = node
+        .syntax()
+        .children_with_tokens()
+        .filter(|child| !(child.kind() == COMMENT || child.kind() == ATTR))
+        .map(|node| node.to_string())
+        .collect();
+
+    label.trim().to_owned()
+}
+
 pub(crate) fn type_label(node: &ast::TypeAliasDef) -> String {
     let label: String = node
         .syntax()
@@ -56,6 +67,19 @@ pub(crate) fn generic_parameters<N: TypeParamsOwner>(node: &N) -> Vec<String> {
     res
 }
 
+/// The generic parameters of `node`, like `<K, V, S = RandomState>`, if some
+/// of them have a default.
+pub(crate) fn generic_params_with_defaults<N: TypeParamsOwner>(node: &N) -> Option<String> {
+    let type_params = node.type_param_list()?;
+    let has_default = type_params.type_params().any(|p| p.default_type().is_some())
+        || type_params.const_params().any(|p| p.default_val().is_some());
+    if !has_default {
+        return None;
+    }
+    let params = type_params.generic_params().map(|p| p.syntax().text().to_string());
+    Some(format!("<{}>", params.sep_by(", ")))
+}
+
 pub(crate) fn where_predicates<N: TypeParamsOwner>(node: &N) -> Vec<String> {
     let mut res = vec![];
     if let Some(clause) = node.where_clause() {
@@ -289,14 +289,16 @@ impl Display for FunctionSignature {
             write!(f, "{} ", t)?;
         }
 
-        if self.qualifier.is_async {
-            write!(f, "async ")?;
-        }
-
+        // Qualifiers go in the order the grammar requires, like
+        // `const unsafe extern "C" fn`.
         if self.qualifier.is_const {
             write!(f, "const ")?;
         }
 
+        if self.qualifier.is_async {
+            write!(f, "async ")?;
+        }
+
         if self.qualifier.is_unsafe {
             write!(f, "unsafe ")?;
         }
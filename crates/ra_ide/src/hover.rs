@@ -301,7 +301,11 @@ fn definition_owner_name(db: &RootDatabase, def: &Definition) -> Option<String>
     .map(|name| name.to_string())
 }
 
-fn determine_mod_path(db: &RootDatabase, module: Module, name: Option<String>) -> String {
+pub(crate) fn determine_mod_path(
+    db: &RootDatabase,
+    module: Module,
+    name: Option<String>,
+) -> String {
     once(db.crate_graph()[module.krate().into()].display_name.as_ref().map(ToString::to_string))
         .chain(
             module
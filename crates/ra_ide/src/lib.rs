@@ -60,7 +60,9 @@ use crate::display::ToNav;
 pub use crate::{
     call_hierarchy::CallItem,
     completion::{
-        CompletionConfig, CompletionItem, CompletionItemKind, CompletionScore, InsertTextFormat,
+        CallParens, CallParensPerKind, CompletionConfig, CompletionItem, CompletionItemHook,
+        CompletionItemKind, CompletionRelevance, CompletionScore, DocumentationFormat, ImportEdit,
+        InsertTextFormat, MemberPreference,
     },
     diagnostics::Severity,
     display::{file_structure, FunctionSignature, NavigationTarget, StructureNode},
@@ -469,6 +471,49 @@ impl Analysis {
         self.with_db(|db| completion::completions(db, config, position).map(Into::into))
     }
 
+    /// Computes the single best completion at the given position, for inline
+    /// suggestions.
+    pub fn primary_completion(
+        &self,
+        config: &CompletionConfig,
+        position: FilePosition,
+    ) -> Cancelable<Option<CompletionItem>> {
+        self.with_db(|db| completion::primary_completion(db, config, position))
+    }
+
+    /// Computes the full documentation of the completion item at `index` in
+    /// the list returned by `completions`, if its documentation was summarized.
+    pub fn completion_documentation(
+        &self,
+        config: &CompletionConfig,
+        position: FilePosition,
+        index: usize,
+        label: &str,
+    ) -> Cancelable<Option<Documentation>> {
+        self.with_db(|db| completion::completion_documentation(db, config, position, index, label))
+    }
+
+    /// Computes completions at the given position, handing each item to
+    /// `sink` as soon as it is produced. Returning `false` from `sink` stops
+    /// the completion before the next completer runs.
+    pub fn completions_streaming(
+        &self,
+        config: &CompletionConfig,
+        position: FilePosition,
+        sink: &mut dyn FnMut(CompletionItem) -> bool,
+    ) -> Cancelable<()> {
+        // `with_db` catches the unwinding of cancelled queries, which needs the
+        // closure to be `UnwindSafe`, and a `&mut` isn't, as the sink could be
+        // seen half-updated after unwinding. That's fine: on cancellation the
+        // caller gets `Err(Canceled)` and so knows the items it received are
+        // incomplete, and the sink shares no other state with the analysis.
+        let sink = std::panic::AssertUnwindSafe(sink);
+        self.with_db(move |db| {
+            let sink = sink;
+            completion::completions_streaming(db, config, position, sink.0)
+        })
+    }
+
     /// Computes resolved assists with source changes for the given position.
     pub fn resolved_assists(
         &self,
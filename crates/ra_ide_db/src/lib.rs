@@ -9,6 +9,7 @@ pub mod defs;
 pub mod search;
 pub mod imports_locator;
 pub mod source_change;
+pub mod helpers;
 mod wasm_shims;
 
 use std::sync::Arc;
@@ -70,6 +70,19 @@ fn type_const_param(p: &mut Parser, m: Marker) {
     p.bump(T![const]);
     name(p);
     types::ascription(p);
+
+    // test const_param_default
+    // struct A<const N: usize = 16>;
+    // struct B<const N: usize = { 4 * 4 }>;
+    if p.eat(T![=]) {
+        match p.current() {
+            T!['{'] => expressions::block_expr(p),
+            k if k.is_literal() => {
+                expressions::literal(p);
+            }
+            _ => p.error("expected a block or a literal"),
+        }
+    }
     m.complete(p, CONST_PARAM);
 }
 
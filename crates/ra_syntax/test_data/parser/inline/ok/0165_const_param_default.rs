@@ -0,0 +1,2 @@
+struct A<const N: usize = 16>;
+struct B<const N: usize = { 4 * 4 }>;
@@ -481,7 +481,8 @@ pub(crate) fn handle_completion(
     params: lsp_types::CompletionParams,
 ) -> Result<Option<lsp_types::CompletionResponse>> {
     let _p = profile("handle_completion");
-    let position = from_proto::file_position(&snap, params.text_document_position)?;
+    let text_document_position = params.text_document_position;
+    let position = from_proto::file_position(&snap, text_document_position.clone())?;
     let completion_triggered_after_single_colon = {
         let mut res = false;
         if let Some(ctx) = params.context {
@@ -512,12 +513,55 @@ pub(crate) fn handle_completion(
     let line_endings = snap.file_line_endings(position.file_id);
     let items: Vec<CompletionItem> = items
         .into_iter()
-        .map(|item| to_proto::completion_item(&line_index, line_endings, item))
+        .enumerate()
+        .map(|(index, item)| {
+            let has_more_documentation = item.has_more_documentation();
+            let mut res =
+                to_proto::completion_item(&line_index, line_endings, &snap.config.completion, item);
+            if has_more_documentation {
+                let data =
+                    CompletionResolveData { position: text_document_position.clone(), index };
+                res.data = Some(to_value(data).unwrap());
+            }
+            res
+        })
         .collect();
 
     Ok(Some(items.into()))
 }
 
+/// Identifies a completion item with summarized documentation, so that its
+/// full documentation can be computed when the item is resolved.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionResolveData {
+    position: lsp_types::TextDocumentPositionParams,
+    index: usize,
+}
+
+pub(crate) fn handle_completion_resolve(
+    snap: GlobalStateSnapshot,
+    mut item: CompletionItem,
+) -> Result<CompletionItem> {
+    let _p = profile("handle_completion_resolve");
+    let data = match item.data.take() {
+        Some(it) => it,
+        None => return Ok(item),
+    };
+    let resolve = from_json::<CompletionResolveData>("CompletionResolveData", data)?;
+    let position = from_proto::file_position(&snap, resolve.position)?;
+    let documentation = snap.analysis.completion_documentation(
+        &snap.config.completion,
+        position,
+        resolve.index,
+        &item.label,
+    )?;
+    if let Some(documentation) = documentation {
+        item.documentation = Some(to_proto::documentation(documentation));
+    }
+    Ok(item)
+}
+
 pub(crate) fn handle_folding_range(
     snap: GlobalStateSnapshot,
     params: FoldingRangeParams,
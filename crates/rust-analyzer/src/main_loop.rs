@@ -357,6 +357,7 @@ impl GlobalState {
             .on::<lsp_types::request::GotoImplementation>(handlers::handle_goto_implementation)?
             .on::<lsp_types::request::GotoTypeDefinition>(handlers::handle_goto_type_definition)?
             .on::<lsp_types::request::Completion>(handlers::handle_completion)?
+            .on::<lsp_types::request::ResolveCompletionItem>(handlers::handle_completion_resolve)?
             .on::<lsp_types::request::CodeLensRequest>(handlers::handle_code_lens)?
             .on::<lsp_types::request::CodeLensResolve>(handlers::handle_code_lens_resolve)?
             .on::<lsp_types::request::FoldingRangeRequest>(handlers::handle_folding_range)?
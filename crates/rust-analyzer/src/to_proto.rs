@@ -4,10 +4,10 @@ use std::path::{self, Path};
 use itertools::Itertools;
 use ra_db::{FileId, FileRange};
 use ra_ide::{
-    Assist, AssistKind, CompletionItem, CompletionItemKind, Documentation, FileSystemEdit, Fold,
-    FoldKind, FunctionSignature, Highlight, HighlightModifier, HighlightTag, HighlightedRange,
-    Indel, InlayHint, InlayKind, InsertTextFormat, LineIndex, NavigationTarget, ReferenceAccess,
-    ResolvedAssist, Runnable, Severity, SourceChange, SourceFileEdit, TextEdit,
+    Assist, AssistKind, CompletionConfig, CompletionItem, CompletionItemKind, Documentation,
+    FileSystemEdit, Fold, FoldKind, FunctionSignature, Highlight, HighlightModifier, HighlightTag,
+    HighlightedRange, Indel, InlayHint, InlayKind, InsertTextFormat, LineIndex, NavigationTarget,
+    ReferenceAccess, ResolvedAssist, Runnable, Severity, SourceChange, SourceFileEdit, TextEdit,
 };
 use ra_syntax::{SyntaxKind, TextRange, TextSize};
 
@@ -155,6 +155,7 @@ pub(crate) fn snippet_text_edit_vec(
 pub(crate) fn completion_item(
     line_index: &LineIndex,
     line_endings: LineEndings,
+    config: &CompletionConfig,
     completion_item: CompletionItem,
 ) -> lsp_types::CompletionItem {
     let mut additional_text_edits = Vec::new();
@@ -197,20 +198,27 @@ pub(crate) fn completion_item(
 
     if completion_item.score().is_some() {
         res.preselect = Some(true);
-        // HACK: sort preselect items first
-        res.sort_text = Some(format!(" {}", completion_item.label()));
     }
+    res.sort_text = completion_item.sort_text(config);
 
     if completion_item.deprecated() {
         res.tags = Some(vec![lsp_types::CompletionItemTag::Deprecated])
     }
 
+    // Only one command can be run, and the parameter hints of an inserted call
+    // take priority over completing inside of it.
     if completion_item.trigger_call_info() {
         res.command = Some(lsp_types::Command {
             title: "triggerParameterHints".into(),
             command: "editor.action.triggerParameterHints".into(),
             arguments: None,
         });
+    } else if completion_item.trigger_completion() {
+        res.command = Some(lsp_types::Command {
+            title: "triggerSuggest".into(),
+            command: "editor.action.triggerSuggest".into(),
+            arguments: None,
+        });
     }
 
     res.insert_text_format = Some(insert_text_format(completion_item.insert_text_format()));
@@ -58,6 +58,10 @@ fn check_todo(path: &Path, text: &str) {
         "handlers/add_missing_impl_members.rs",
         "handlers/add_turbo_fish.rs",
         "handlers/generate_function.rs",
+        // Stubs for missing trait items and match arms are completed with a
+        // `todo!()` body.
+        "completion/complete_trait_impl.rs",
+        "completion/complete_match_arms.rs",
         // To support generating `todo!()` in assists, we have `expr_todo()` in ast::make.
         "ast/make.rs",
     ];